@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::bot::resources::{BotController, BotStrategy, OpponentConfig};
+use crate::common::components::{Direction, Position, Wall};
+use crate::common::resources::{ArenaConfig, GameRng};
+use crate::food::components::Food;
+use crate::snake::components::{InputQueue, SnakeHead, Tail};
+use crate::snake::resources::{InputBufferConfig, LocalSnake};
+use crate::snake::{lethal_cells, steer, ticks_until_move};
+use crate::state::GameState;
+
+pub mod resources;
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpponentConfig>().add_system(bot_movement_input.run_in_state(GameState::Running));
+    }
+}
+
+// Colors opponents are drawn in by id, cycling for ids past the last one
+const OPPONENT_COLORS: [Color; 4] = [
+    Color::rgb(0.9, 0.5, 0.2),
+    Color::rgb(0.6, 0.3, 0.9),
+    Color::rgb(0.2, 0.8, 0.8),
+    Color::rgb(0.9, 0.3, 0.6),
+];
+
+/// Color the opponent with `id` is drawn in, before any team tint
+pub fn opponent_color(id: u8) -> Color {
+    OPPONENT_COLORS[id as usize % OPPONENT_COLORS.len()]
+}
+
+const DIRECTIONS: [Direction; 4] = [Direction::Left, Direction::Up, Direction::Right, Direction::Down];
+
+// Picks the next turn of every bot driven snake each time it moves, feeding it into the same queue as player input.
+// That is every opponent, and the local snake too if a BotController is playing it.
+#[allow(clippy::too_many_arguments)]
+fn bot_movement_input(
+    bot: Option<Res<BotController>>,
+    opponents: Res<OpponentConfig>,
+    arena: Res<ArenaConfig>,
+    buffer: Res<InputBufferConfig>,
+    timesteps: Res<FixedTimesteps>,
+    mut rng: ResMut<GameRng>,
+    local_snake: Option<Res<LocalSnake>>,
+    mut moved: Query<(&Position, &SnakeHead, &mut InputQueue), Changed<Position>>,
+    heads: Query<(&Position, &SnakeHead)>,
+    tails: Query<&Position, With<Tail>>,
+    walls: Query<&Position, With<Wall>>,
+    foods: Query<&Position, With<Food>>,
+) {
+    let local_snake = match local_snake {
+        Some(local_snake) => local_snake,
+        None => return,
+    };
+    for (position, head, mut queue) in moved.iter_mut() {
+        let strategy = if head.id != local_snake.0 {
+            opponents.strategy
+        } else if let Some(bot) = &bot {
+            bot.strategy
+        } else {
+            continue;
+        };
+        let candidates: Vec<Direction> =
+            DIRECTIONS.into_iter().filter(|dir| *dir != head.direction.opposite()).collect();
+        let dir = match strategy {
+            BotStrategy::Random => candidates.choose(&mut rng.rng).copied(),
+            BotStrategy::SeekNearestFood => closest_to_food(*position, head.direction, &candidates, &foods, &arena),
+            BotStrategy::AvoidCollision => {
+                let lethal = lethal_cells(heads.iter(), &tails, &walls);
+                let safe: Vec<Direction> = candidates
+                    .into_iter()
+                    .filter(|dir| !lethal.contains_key(&position.wrapped_step(*dir, &arena)))
+                    .collect();
+                closest_to_food(*position, head.direction, &safe, &foods, &arena).or_else(|| safe.first().copied())
+            }
+        };
+        if let Some(dir) = dir {
+            steer(head, &mut queue, dir, &buffer, ticks_until_move(&timesteps, head));
+        }
+    }
+}
+
+/// Candidate direction whose next cell is nearest to any food, preferring to keep going straight on ties
+fn closest_to_food(
+    position: Position,
+    current: Direction,
+    candidates: &[Direction],
+    foods: &Query<&Position, With<Food>>,
+    arena: &ArenaConfig,
+) -> Option<Direction> {
+    candidates
+        .iter()
+        .filter_map(|dir| {
+            let next = position.wrapped_step(*dir, arena);
+            let closest = foods.iter().map(|food| next.distance(*food, arena)).min()?;
+            Some((closest, *dir != current, *dir))
+        })
+        .min_by_key(|(closest, turns, _)| (*closest, *turns))
+        .map(|(_, _, dir)| dir)
+}
@@ -0,0 +1,53 @@
+use std::str::FromStr;
+
+/// How a bot picks its next turn
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BotStrategy {
+    /// Any direction that doesn't reverse the snake
+    Random,
+    /// Greedily head towards the closest food, even into danger
+    SeekNearestFood,
+    /// Head towards the closest food, but never into a wall or snake body if there is a way out
+    AvoidCollision,
+}
+
+impl FromStr for BotStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "seek" => Ok(Self::SeekNearestFood),
+            "avoid" => Ok(Self::AvoidCollision),
+            _ => Err(format!(
+                "Unknown bot strategy '{}', expected one of: random, seek, avoid",
+                s
+            )),
+        }
+    }
+}
+
+/// Insert to have a bot drive the local snake instead of keyboard and gamepad input
+pub struct BotController {
+    pub strategy: BotStrategy,
+}
+
+/// Bot driven snakes spawned alongside the player's at the start of each game, so there is someone to play against.
+/// They take the ids after the local snake's.
+pub struct OpponentConfig {
+    pub count: u8,
+    pub strategy: BotStrategy,
+    /// Splits the snakes into two teams instead of everyone playing alone. Opponents with odd ids play on team 1 and
+    /// the rest on team 0, which is also the player's unless [`crate::snake::resources::PlayerTeam`] picks another.
+    pub teams: bool,
+}
+
+impl Default for OpponentConfig {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            strategy: BotStrategy::AvoidCollision,
+            teams: false,
+        }
+    }
+}
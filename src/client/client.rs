@@ -1,4 +1,3 @@
-use quinn::Endpoint;
 use crate::common::quinn_helpers::make_client_endpoint;
 
 // pub fn client_main() {
@@ -18,7 +17,7 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let server_addr = "127.0.0.1:5000".parse().unwrap();
     let client_addr = "127.0.0.1:5001".parse().unwrap();
     // Bind this endpoint to a UDP socket on the given client address.
-    let mut endpoint = make_client_endpoint(client_addr, &[])?;
+    let endpoint = make_client_endpoint(client_addr, &[])?;
 
     // Connect to the server passing in the server name which is supposed to be in the server certificate.
     let connection = endpoint.connect(server_addr, "localhost")?.await?;
@@ -1,83 +1,496 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use bevy::ecs::system::EntityCommands;
+use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use iyes_loopless::prelude::*;
 
 use components::Size;
 
-use crate::common::components::Position;
-use crate::common::constants::{ARENA_HEIGHT, ARENA_WIDTH};
-use crate::snake::components::SnakeHead;
-use crate::snake::spawn_snake;
+use crate::bot::opponent_color;
+use crate::bot::resources::{BotController, OpponentConfig};
+use crate::common::components::{InterpolationBuffer, Obstacle, Position, PositionState, RenderPosition, Wall};
+use crate::common::constants::{
+    MAX_ARENA_SIZE, MAX_CELL_SIZE, MAX_INTERPOLATION_BUFFER_MS, MAX_OPPONENTS, MAX_TICK_RATE_HZ, MIN_ARENA_SIZE,
+    MIN_CELL_SIZE, MIN_INTERPOLATION_BUFFER_MS, MIN_TICK_RATE_HZ,
+};
+use crate::common::resources::{
+    ArenaConfig, ArenaMode, CameraFollowConfig, CameraZoom, Countdown, CountdownConfig, GameRng, InterpolationConfig,
+    LogThrottle, ObstacleConfig, RenderConfig, Spectator, SpectatorKeybinds, SpectatorTarget, Theme, ZoomConfig,
+};
+use crate::food::components::Food;
+use crate::snake::components::{SnakeHead, Tail};
+use crate::snake::events::{GameAudioEvent, GameResultEvent, SnakeDeathEvent};
+use crate::snake::resources::{
+    ClientTickRate, ColorblindMode, GamepadDeadzone, LocalSnake, MovementStep, MovementTick, PlayerColorPreference,
+    PlayerName, PlayerTeam, RespawnConfig, RespawnQueue, Scoreboard, SnakeRegistry, SnakeSpawnConfig,
+    TailGradientConfig,
+};
+use crate::snake::{spawn_position, spawn_snake, SnakeSpawn, MAX_SPEED, MOVEMENT_TIMESTEP};
 use crate::state::GameState;
 
 pub mod components;
 pub mod constants;
 pub mod quinn_helpers;
+pub mod resources;
+pub mod snapshot;
 
 pub struct CommonPlugin;
 
 impl Plugin for CommonPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_camera).add_enter_system(GameState::PreGame, pre_game).add_system_set_to_stage(
-            CoreStage::PostUpdate,
-            ConditionSet::new()
-                .run_in_state(GameState::Running)
-                .with_system(position_translation)
-                .with_system(size_scaling)
-                .into(),
+        // Honour a tick rate inserted before the plugin for the initial step
+        let tick_rate = *app.world.get_resource_or_insert_with(ClientTickRate::default);
+        app.add_loopless_state(GameState::MainMenu)
+            // Created here rather than by the snake plugin so food can add its per step systems to it too
+            .add_fixed_timestep(tick_rate.step() / MAX_SPEED, MOVEMENT_TIMESTEP)
+            .init_resource::<ArenaConfig>()
+            .init_resource::<ObstacleConfig>()
+            .init_resource::<RenderConfig>()
+            .init_resource::<GameRng>()
+            .init_resource::<InterpolationConfig>()
+            .init_resource::<CameraFollowConfig>()
+            .init_resource::<ZoomConfig>()
+            .init_resource::<CountdownConfig>()
+            .init_resource::<SpectatorKeybinds>()
+            .init_resource::<CameraZoom>()
+            .init_resource::<Theme>()
+            .init_resource::<LogThrottle>()
+            .add_startup_system(setup_camera)
+            // After startup so settings loaded from disk are checked too
+            .add_startup_system_to_stage(StartupStage::PostStartup, validate_configs)
+            .add_enter_system(GameState::PreGame, pre_game)
+            .add_enter_system(GameState::PreGame, spawn_arena)
+            // Nothing moves during the countdown, so place everything just spawned once up front
+            .add_enter_system(GameState::Countdown, sync_transforms)
+            .add_enter_system(GameState::Countdown, size_scaling)
+            .add_system(countdown.run_in_state(GameState::Countdown))
+            .add_system(end_game.run_in_state(GameState::Running))
+            .add_enter_system(GameState::GameOver, despawn_game_entities)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_positions.run_in_state(GameState::Running).label(PositionState::Interpolate),
+            )
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                ConditionSet::new()
+                    .run_in_state(GameState::Running)
+                    .label(PositionState::Translation)
+                    .after(PositionState::Interpolate)
+                    .with_system(sync_transforms)
+                    .with_system(size_scaling)
+                    .into(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera_follow.run_in_state(GameState::Running).after(PositionState::Translation),
+            )
+            .add_system(
+                spectator_cycle
+                    .run_in_state(GameState::Running)
+                    .run_if_resource_exists::<Spectator>()
+                    .run_if_resource_exists::<Input<KeyCode>>(),
+            )
+            // Headless runs have no mouse or keyboard to zoom with
+            .add_system(camera_zoom.run_in_state(GameState::Running).run_if_resource_exists::<Events<MouseWheel>>())
+            .add_system(cycle_theme.run_in_state(GameState::Running).run_if_resource_exists::<Input<KeyCode>>())
+            .add_system(apply_theme);
+    }
+}
+
+fn size_scaling(render: Res<RenderConfig>, mut q: Query<(&Size, &mut Transform)>) {
+    for (sprite_size, mut transform) in q.iter_mut() {
+        transform.scale = Vec3::new(
+            (sprite_size.width * render.cell_size - render.gap).max(0.),
+            (sprite_size.height * render.cell_size - render.gap).max(0.),
+            1.0,
         );
     }
 }
 
-fn size_scaling(windows: Res<Windows>, mut q: Query<(&Size, &mut Transform)>) {
-    if let Some(window) = windows.get_primary() {
-        for (sprite_size, mut transform) in q.iter_mut() {
-            transform.scale = Vec3::new(
-                sprite_size.width / ARENA_WIDTH as f32 * window.width() as f32,
-                sprite_size.height / ARENA_HEIGHT as f32 * window.height() as f32,
-                1.0,
-            );
+fn interpolate_positions(
+    time: Res<Time>,
+    config: Res<InterpolationConfig>,
+    mut q: Query<(&Position, &mut InterpolationBuffer, &mut RenderPosition)>,
+) {
+    let buffer_secs = config.buffer_ms / 1000.;
+    for (pos, mut buffer, mut render_pos) in q.iter_mut() {
+        if *pos != buffer.current {
+            buffer.previous = buffer.current;
+            buffer.current = *pos;
+            buffer.elapsed = Duration::ZERO;
+        } else {
+            buffer.elapsed += time.delta();
         }
+
+        let (from, to) = (buffer.previous, buffer.current);
+        let glide_secs = buffer_secs / buffer.speed;
+        // Snap instead of gliding across the whole arena when wrapping around an edge
+        let t = if glide_secs <= 0. || (to.x - from.x).abs() > 1 || (to.y - from.y).abs() > 1 {
+            1.
+        } else {
+            (buffer.elapsed.as_secs_f32() / glide_secs).min(1.)
+        };
+        render_pos.x = from.x as f32 + (to.x - from.x) as f32 * t;
+        render_pos.y = from.y as f32 + (to.y - from.y) as f32 * t;
+    }
+}
+
+// The one place grid entities' translations are written. Runs after everything that moves them, i.e. movement on its
+// fixed timestep and interpolate_positions, so each frame draws the RenderPosition if there is one and the Position
+// otherwise. Heads sit above tails and everything else.
+fn sync_transforms(
+    arena: Res<ArenaConfig>,
+    render: Res<RenderConfig>,
+    mut q: Query<(&Position, Option<&RenderPosition>, &mut Transform, Option<&SnakeHead>)>,
+) {
+    for (pos, render_pos, mut transform, head) in q.iter_mut() {
+        let render_pos = render_pos.copied().unwrap_or_else(|| RenderPosition::from(*pos));
+        let z = if head.is_some() { 1.0 } else { 0.0 };
+        transform.translation = cell_to_world(render_pos, &arena, &render).extend(z);
     }
 }
 
-fn position_translation(
-    windows: Res<Windows>,
-    mut q: Query<(&mut Position, &mut Transform, Option<&SnakeHead>)>, /*, Changed<Position>> */
+/// Where a (possibly fractional) grid position is drawn. Cell centers are cell_size apart with the arena centered on
+/// the origin, and nothing is ever drawn outside the arena, even for an out of bounds position.
+pub fn cell_to_world(pos: RenderPosition, arena: &ArenaConfig, render: &RenderConfig) -> Vec2 {
+    let convert =
+        |pos: f32, bound: i32| (pos.clamp(0., (bound - 1) as f32) - (bound as f32 - 1.) / 2.) * render.cell_size;
+    Vec2::new(convert(pos.x, arena.width), convert(pos.y, arena.height))
+}
+
+// Glides the camera towards the snake it follows, or back to the arena's center when following is off. That is the
+// local snake, or for a spectator, whichever snake the spectator target picks.
+fn camera_follow(
+    time: Res<Time>,
+    config: Res<CameraFollowConfig>,
+    spectator: Option<Res<Spectator>>,
+    local_snake: Option<Res<LocalSnake>>,
+    registry: Res<SnakeRegistry>,
+    heads: Query<(&SnakeHead, &Transform), Without<Camera2d>>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
 ) {
-    fn convert(pos: f32, bound_window: f32, bound_game: f32) -> f32 {
-        let tile_size = bound_window / bound_game;
-        pos / bound_game * bound_window - (bound_window / 2.) + (tile_size / 2.)
-    }
-    if let Some(window) = windows.get_primary() {
-        for (mut pos, mut transform, head) in q.iter_mut() {
-            if pos.x >= ARENA_WIDTH as i32 {
-                pos.x = 0;
-            } else if pos.x < 0 {
-                pos.x = ARENA_WIDTH as i32 - 1;
-            }
-
-            if pos.y >= ARENA_HEIGHT as i32 {
-                pos.y = 0;
-            } else if pos.y < 0 {
-                pos.y = ARENA_HEIGHT as i32 - 1;
-            }
-
-            let z = if head.is_some() { 1.0 } else { 0.0 };
-
-            transform.translation = Vec3::new(
-                convert(pos.x as f32, window.width() as f32, ARENA_WIDTH as f32),
-                convert(pos.y as f32, window.height() as f32, ARENA_HEIGHT as f32),
-                z,
-            );
+    let followed = if spectator.is_some() {
+        match config.spectator_target {
+            SpectatorTarget::Leader => heads
+                .iter()
+                .max_by_key(|(head, _)| (head.tail.len(), std::cmp::Reverse(head.id)))
+                .map(|(head, _)| head.id),
+            SpectatorTarget::Fixed => None,
+            SpectatorTarget::SnakeId(id) => Some(id),
         }
+    } else if config.enabled {
+        local_snake.map(|local_snake| local_snake.0)
+    } else {
+        None
+    };
+    let target = match followed {
+        Some(id) => match registry.snakes.get(&id).and_then(|head| heads.get(*head).ok()) {
+            Some((_, transform)) => transform.translation.truncate(),
+            None => return,
+        },
+        None => Vec2::ZERO,
+    };
+
+    let t = (config.smoothing * time.delta_seconds()).clamp(0., 1.);
+    for mut camera in cameras.iter_mut() {
+        let position = camera.translation.truncate().lerp(target, t);
+        camera.translation.x = position.x;
+        camera.translation.y = position.y;
+    }
+}
+
+// Switches a spectator watching a particular snake to the next or previous live one, by id, wrapping around
+fn spectator_cycle(
+    keys: Res<Input<KeyCode>>,
+    keybinds: Res<SpectatorKeybinds>,
+    registry: Res<SnakeRegistry>,
+    mut config: ResMut<CameraFollowConfig>,
+) {
+    let current = match config.spectator_target {
+        SpectatorTarget::SnakeId(id) => id,
+        _ => return,
+    };
+    let forward = match (keys.just_pressed(keybinds.next), keys.just_pressed(keybinds.previous)) {
+        (true, false) => true,
+        (false, true) => false,
+        _ => return,
+    };
+    // Dead snakes are gone from the registry, so they are skipped
+    let mut ids: Vec<u8> = registry.snakes.keys().copied().collect();
+    ids.sort_unstable();
+    let next = if forward {
+        ids.iter().find(|id| **id > current).or_else(|| ids.first())
+    } else {
+        ids.iter().rev().find(|id| **id < current).or_else(|| ids.last())
+    };
+    if let Some(next) = next {
+        config.spectator_target = SpectatorTarget::SnakeId(*next);
+    }
+}
+
+// Zooms with the mouse wheel or +/-, easing the camera towards the new zoom over the next few frames
+fn camera_zoom(
+    time: Res<Time>,
+    config: Res<ZoomConfig>,
+    keys: Res<Input<KeyCode>>,
+    mut wheel: EventReader<MouseWheel>,
+    mut zoom: ResMut<CameraZoom>,
+    mut projections: Query<&mut OrthographicProjection, With<Camera2d>>,
+) {
+    // Positive is zooming in, one per wheel notch or key press
+    let mut steps: f32 = wheel.iter().map(|event| event.y.signum()).sum();
+    if keys.any_just_pressed([KeyCode::Equals, KeyCode::NumpadAdd]) {
+        steps += 1.;
+    }
+    if keys.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        steps -= 1.;
+    }
+    if steps != 0. {
+        zoom.0 = (zoom.0 * (1. - config.step).powf(steps)).clamp(config.min, config.max);
+    }
+
+    let t = (config.smoothing * time.delta_seconds()).clamp(0., 1.);
+    for mut projection in projections.iter_mut() {
+        if projection.scale != zoom.0 {
+            projection.scale += (zoom.0 - projection.scale) * t;
+        }
+    }
+}
+
+// Switches to the next built-in theme on T
+fn cycle_theme(keys: Res<Input<KeyCode>>, mut theme: ResMut<Theme>) {
+    if keys.just_pressed(KeyCode::T) {
+        *theme = theme.next();
+    }
+}
+
+// Recolors the background and walls whenever the theme changes. The grid overlay redraws itself.
+fn apply_theme(theme: Res<Theme>, clear_color: Option<ResMut<ClearColor>>, mut walls: Query<&mut Sprite, With<Wall>>) {
+    if !theme.is_changed() {
+        return;
+    }
+    // Headless runs have nothing to clear
+    if let Some(mut clear_color) = clear_color {
+        clear_color.0 = theme.background_color;
+    }
+    for mut sprite in walls.iter_mut() {
+        sprite.color = theme.wall_color;
+    }
+}
+
+// Clamps config resources into sane ranges, warning about anything that was out of range
+#[allow(clippy::too_many_arguments)]
+fn validate_configs(
+    mut arena: ResMut<ArenaConfig>,
+    mut interpolation: ResMut<InterpolationConfig>,
+    mut render: ResMut<RenderConfig>,
+    mut tick_rate: ResMut<ClientTickRate>,
+    mut deadzone: ResMut<GamepadDeadzone>,
+    zoom_config: Res<ZoomConfig>,
+    mut zoom: ResMut<CameraZoom>,
+    mut opponents: ResMut<OpponentConfig>,
+) {
+    // Resources are only written when a value actually needs clamping, so nothing else sees a change
+    if let Some(width) = clamped("Arena width", arena.width, MIN_ARENA_SIZE, MAX_ARENA_SIZE) {
+        arena.width = width;
+    }
+    if let Some(height) = clamped("Arena height", arena.height, MIN_ARENA_SIZE, MAX_ARENA_SIZE) {
+        arena.height = height;
     }
+    if let Some(buffer_ms) = clamped(
+        "Interpolation buffer",
+        interpolation.buffer_ms,
+        MIN_INTERPOLATION_BUFFER_MS,
+        MAX_INTERPOLATION_BUFFER_MS,
+    ) {
+        interpolation.buffer_ms = buffer_ms;
+    }
+    if let Some(cell_size) = clamped("Cell size", render.cell_size, MIN_CELL_SIZE, MAX_CELL_SIZE) {
+        render.cell_size = cell_size;
+    }
+    if let Some(hz) = clamped("Tick rate", tick_rate.hz, MIN_TICK_RATE_HZ, MAX_TICK_RATE_HZ) {
+        tick_rate.hz = hz;
+    }
+    if let Some(value) = clamped("Gamepad deadzone", deadzone.0, 0., 1.) {
+        deadzone.0 = value;
+    }
+    if let Some(value) = clamped("Camera zoom", zoom.0, zoom_config.min, zoom_config.max) {
+        zoom.0 = value;
+    }
+    if let Some(count) = clamped("Opponents", opponents.count, 0, MAX_OPPONENTS) {
+        opponents.count = count;
+    }
+}
+
+/// `value` clamped into `[min, max]`, or None if it was already in range. NaN is clamped to `min`.
+fn clamped<T: PartialOrd + Copy + std::fmt::Debug>(name: &str, value: T, min: T, max: T) -> Option<T> {
+    let clamped = match (value.partial_cmp(&min), value.partial_cmp(&max)) {
+        (Some(Ordering::Less) | None, _) => min,
+        (_, Some(Ordering::Greater)) => max,
+        _ => return None,
+    };
+    warn!(
+        "{} of {:?} is outside [{:?}, {:?}], clamping it to {:?}",
+        name, value, min, max, clamped
+    );
+    Some(clamped)
 }
 
 fn setup_camera(mut commands: Commands) {
     commands.spawn_bundle(Camera2dBundle::default());
 }
 
-fn pre_game(mut commands: Commands) {
-    commands.insert_resource(NextState(GameState::Running));
-    spawn_snake(commands);
+#[allow(clippy::too_many_arguments)]
+fn pre_game(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    color_preference: Res<PlayerColorPreference>,
+    player_name: Res<PlayerName>,
+    player_team: Res<PlayerTeam>,
+    opponents: Res<OpponentConfig>,
+    spawn_config: Res<SnakeSpawnConfig>,
+    tail_gradient: Res<TailGradientConfig>,
+    colorblind_mode: Res<ColorblindMode>,
+    countdown_config: Res<CountdownConfig>,
+    spectator: Option<Res<Spectator>>,
+    bot: Option<Res<BotController>>,
+    mut registry: ResMut<SnakeRegistry>,
+    mut rng: ResMut<GameRng>,
+    mut audio_events: EventWriter<GameAudioEvent>,
+) {
+    commands.insert_resource(NextState(GameState::Countdown));
+    commands.insert_resource(Countdown::new(countdown_config.secs));
+    commands.insert_resource(LocalSnake(0));
+    commands.insert_resource(MovementTick::default());
+    commands.insert_resource(MovementStep::default());
+    rng.reseed();
+    info!("Starting game with seed {}", rng.seed);
+    if !color_preference.is_valid() {
+        warn!(
+            "Snake color ({}, {}, {}) is outside [0, 1], clamping it",
+            color_preference.r, color_preference.g, color_preference.b
+        );
+    }
+    let team = |id: u8| opponents.teams.then_some(id % 2);
+    // A spectator can't steer, so without a bot to play it the local snake sits the game out rather than running
+    // straight until it dies
+    let local = (spectator.is_none() || bot.is_some()).then(|| {
+        let team = player_team.0.or_else(|| team(0));
+        (0, player_name.0.clone(), color_preference.color(), team)
+    });
+    let snakes =
+        local.into_iter().chain((1..=opponents.count).map(|id| (id, String::new(), opponent_color(id), team(id))));
+
+    let mut scoreboard = Scoreboard::default();
+    for (id, name, color, team) in snakes {
+        scoreboard.scores.insert(id, 0);
+        if let Some(team) = team {
+            scoreboard.teams.insert(id, team);
+        }
+        spawn_snake(
+            &mut commands,
+            SnakeSpawn {
+                id,
+                name,
+                color,
+                team,
+                position: spawn_position(id, &arena),
+                initial_tail: spawn_config.initial_tail,
+            },
+            &arena,
+            &tail_gradient,
+            *colorblind_mode,
+            &mut registry,
+        );
+    }
+    commands.insert_resource(scoreboard);
+    audio_events.send(GameAudioEvent::Spawned);
+}
+
+// Border walls and obstacles for the game about to start
+fn spawn_arena(mut commands: Commands, arena: Res<ArenaConfig>, theme: Res<Theme>, obstacles: Res<ObstacleConfig>) {
+    if arena.mode == ArenaMode::SolidWalls {
+        spawn_walls(&mut commands, &arena, theme.wall_color);
+    }
+    for position in obstacles.positions.iter() {
+        if (0..arena.width).contains(&position.x) && (0..arena.height).contains(&position.y) {
+            spawn_wall(&mut commands, *position, theme.wall_color).insert(Obstacle);
+        } else {
+            warn!("Obstacle at {:?} is outside the arena, skipping it", position);
+        }
+    }
+}
+
+fn spawn_walls(commands: &mut Commands, arena: &ArenaConfig, color: Color) {
+    let border = (0..arena.width)
+        .flat_map(|x| [Position { x, y: 0 }, Position { x, y: arena.height - 1 }])
+        .chain((1..arena.height - 1).flat_map(|y| [Position { x: 0, y }, Position { x: arena.width - 1, y }]));
+    for position in border {
+        spawn_wall(commands, position, color);
+    }
+}
+
+fn spawn_wall<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    position: Position,
+    color: Color,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut wall = commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite { color, ..default() },
+        ..default()
+    });
+    wall.insert(Wall).insert(position).insert(Size::square(1.0));
+    wall
+}
+
+// The round is over once at most one snake is left standing
+fn end_game(
+    mut commands: Commands,
+    mut death_events: EventReader<SnakeDeathEvent>,
+    mut result_events: EventWriter<GameResultEvent>,
+    respawn_config: Res<RespawnConfig>,
+    heads: Query<&SnakeHead>,
+) {
+    // Dead snakes come back, so there is never a last one standing
+    if respawn_config.enabled {
+        return;
+    }
+    let dead: Vec<u8> = death_events.iter().map(|event| event.id).collect();
+    if dead.is_empty() {
+        return;
+    }
+    let survivors: Vec<u8> = heads.iter().map(|head| head.id).filter(|id| !dead.contains(id)).collect();
+    match survivors[..] {
+        [] => result_events.send(GameResultEvent { winner: None }),
+        [winner] => result_events.send(GameResultEvent { winner: Some(winner) }),
+        _ => return,
+    }
+    commands.insert_resource(NextState(GameState::GameOver));
+}
+
+// Starts the game once the countdown runs out
+fn countdown(mut commands: Commands, time: Res<Time>, mut countdown: ResMut<Countdown>) {
+    if countdown.remaining > 0 && countdown.timer.tick(time.delta()).just_finished() {
+        countdown.remaining -= 1;
+    }
+    if countdown.remaining == 0 {
+        commands.remove_resource::<Countdown>();
+        commands.insert_resource(NextState(GameState::Running));
+    }
+}
+
+// Clear out the arena so a restart begins clean
+fn despawn_game_entities(
+    mut commands: Commands,
+    mut registry: ResMut<SnakeRegistry>,
+    mut respawn_queue: ResMut<RespawnQueue>,
+    entities: Query<Entity, Or<(With<SnakeHead>, With<Tail>, With<Food>, With<Wall>)>>,
+) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    registry.snakes.clear();
+    respawn_queue.pending.clear();
 }
@@ -1,11 +1,72 @@
-use bevy::prelude::Component;
+use std::time::Duration;
 
-#[derive(Component, Clone, Copy, PartialEq, Eq, Hash)]
+use bevy::prelude::{Component, SystemLabel};
+
+use crate::common::resources::{ArenaConfig, ArenaMode};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum PositionState {
+    Interpolate,
+    Translation,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
+impl Position {
+    /// The neighbouring cell in `direction`, which may be outside the arena
+    pub fn step(self, direction: Direction) -> Self {
+        match direction {
+            Direction::Left => Self { x: self.x - 1, ..self },
+            Direction::Up => Self { y: self.y + 1, ..self },
+            Direction::Right => Self { x: self.x + 1, ..self },
+            Direction::Down => Self { y: self.y - 1, ..self },
+            #[cfg(feature = "diagonal")]
+            Direction::UpLeft => self.step(Direction::Up).step(Direction::Left),
+            #[cfg(feature = "diagonal")]
+            Direction::UpRight => self.step(Direction::Up).step(Direction::Right),
+            #[cfg(feature = "diagonal")]
+            Direction::DownLeft => self.step(Direction::Down).step(Direction::Left),
+            #[cfg(feature = "diagonal")]
+            Direction::DownRight => self.step(Direction::Down).step(Direction::Right),
+        }
+    }
+
+    /// The neighbouring cell in `direction`, coming out the opposite edge if the arena wraps
+    pub fn wrapped_step(self, direction: Direction, arena: &ArenaConfig) -> Self {
+        let next = self.step(direction);
+        match arena.mode {
+            ArenaMode::Wrap => Self {
+                x: next.x.rem_euclid(arena.width),
+                y: next.y.rem_euclid(arena.height),
+            },
+            ArenaMode::SolidWalls => next,
+        }
+    }
+
+    /// Moves needed to get from one cell to another, taking shortcuts across the edges in a wrapping arena
+    pub fn distance(self, to: Position, arena: &ArenaConfig) -> i32 {
+        let dx = (self.x - to.x).abs();
+        let dy = (self.y - to.y).abs();
+        match arena.mode {
+            ArenaMode::Wrap => dx.min(arena.width - dx) + dy.min(arena.height - dy),
+            ArenaMode::SolidWalls => dx + dy,
+        }
+    }
+}
+
+// Tag component for cells that kill a snake running into them, i.e. the arena boundary in ArenaMode::SolidWalls
+// and any Obstacle
+#[derive(Component)]
+pub struct Wall;
+
+// Tag component for walls placed inside the arena from ObstacleConfig
+#[derive(Component)]
+pub struct Obstacle;
+
 #[derive(Component)]
 pub struct Size {
     pub width: f32,
@@ -24,6 +85,14 @@ pub enum Direction {
     Up,
     Right,
     Down,
+    #[cfg(feature = "diagonal")]
+    UpLeft,
+    #[cfg(feature = "diagonal")]
+    UpRight,
+    #[cfg(feature = "diagonal")]
+    DownLeft,
+    #[cfg(feature = "diagonal")]
+    DownRight,
 }
 
 impl Direction {
@@ -33,6 +102,141 @@ impl Direction {
             Self::Right => Self::Left,
             Self::Up => Self::Down,
             Self::Down => Self::Up,
+            #[cfg(feature = "diagonal")]
+            Self::UpLeft => Self::DownRight,
+            #[cfg(feature = "diagonal")]
+            Self::DownRight => Self::UpLeft,
+            #[cfg(feature = "diagonal")]
+            Self::UpRight => Self::DownLeft,
+            #[cfg(feature = "diagonal")]
+            Self::DownLeft => Self::UpRight,
+        }
+    }
+
+    /// Mirrored left to right, so horizontal directions flip and vertical ones don't change
+    pub fn mirrored_x(self) -> Self {
+        match self {
+            Self::Left | Self::Right => self.opposite(),
+            Self::Up | Self::Down => self,
+            #[cfg(feature = "diagonal")]
+            Self::UpLeft => Self::UpRight,
+            #[cfg(feature = "diagonal")]
+            Self::UpRight => Self::UpLeft,
+            #[cfg(feature = "diagonal")]
+            Self::DownLeft => Self::DownRight,
+            #[cfg(feature = "diagonal")]
+            Self::DownRight => Self::DownLeft,
+        }
+    }
+
+    /// Mirrored top to bottom, so vertical directions flip and horizontal ones don't change
+    pub fn mirrored_y(self) -> Self {
+        self.mirrored_x().opposite()
+    }
+
+    /// Diagonal between a horizontal and a vertical direction, or None if they aren't perpendicular
+    #[cfg(feature = "diagonal")]
+    pub fn diagonal(horizontal: Direction, vertical: Direction) -> Option<Self> {
+        match (horizontal, vertical) {
+            (Self::Left, Self::Up) => Some(Self::UpLeft),
+            (Self::Right, Self::Up) => Some(Self::UpRight),
+            (Self::Left, Self::Down) => Some(Self::DownLeft),
+            (Self::Right, Self::Down) => Some(Self::DownRight),
+            _ => None,
+        }
+    }
+}
+
+/// Grid position actually drawn on screen, smoothed between an entity's last two [`Position`]s
+#[derive(Component, Clone, Copy, PartialEq)]
+pub struct RenderPosition {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl From<Position> for RenderPosition {
+    fn from(position: Position) -> Self {
+        Self {
+            x: position.x as f32,
+            y: position.y as f32,
+        }
+    }
+}
+
+/// Last two [`Position`]s an entity was at, and how long it has been since it moved into the latest one
+#[derive(Component)]
+pub struct InterpolationBuffer {
+    pub previous: Position,
+    pub current: Position,
+    pub elapsed: Duration,
+    /// Cells per tick the entity moves at, which shortens the glide between cells for faster snakes
+    pub speed: f32,
+}
+
+impl InterpolationBuffer {
+    pub fn new(position: Position) -> Self {
+        Self {
+            previous: position,
+            current: position,
+            elapsed: Duration::ZERO,
+            speed: 1.0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arena(mode: ArenaMode) -> ArenaConfig {
+        ArenaConfig {
+            width: 10,
+            height: 8,
+            mode,
+        }
+    }
+
+    #[test]
+    fn step_moves_one_cell() {
+        let position = Position { x: 4, y: 4 };
+        assert_eq!(position.step(Direction::Left), Position { x: 3, y: 4 });
+        assert_eq!(position.step(Direction::Up), Position { x: 4, y: 5 });
+        assert_eq!(position.step(Direction::Right), Position { x: 5, y: 4 });
+        assert_eq!(position.step(Direction::Down), Position { x: 4, y: 3 });
+    }
+
+    #[test]
+    fn wrapped_step_wraps_at_every_edge() {
+        let arena = arena(ArenaMode::Wrap);
+        let cases = [
+            (Position { x: 0, y: 3 }, Direction::Left, Position { x: 9, y: 3 }),
+            (Position { x: 9, y: 3 }, Direction::Right, Position { x: 0, y: 3 }),
+            (Position { x: 2, y: 0 }, Direction::Down, Position { x: 2, y: 7 }),
+            (Position { x: 2, y: 7 }, Direction::Up, Position { x: 2, y: 0 }),
+            // Away from the edges it is a plain step
+            (Position { x: 5, y: 5 }, Direction::Up, Position { x: 5, y: 6 }),
+        ];
+        for (from, direction, to) in cases {
+            assert_eq!(
+                from.wrapped_step(direction, &arena),
+                to,
+                "{:?} from {:?}",
+                direction,
+                from
+            );
+        }
+    }
+
+    #[test]
+    fn wrapped_step_leaves_solid_arena() {
+        let arena = arena(ArenaMode::SolidWalls);
+        assert_eq!(
+            Position { x: 0, y: 3 }.wrapped_step(Direction::Left, &arena),
+            Position { x: -1, y: 3 }
+        );
+        assert_eq!(
+            Position { x: 2, y: 7 }.wrapped_step(Direction::Up, &arena),
+            Position { x: 2, y: 8 }
+        );
+    }
+}
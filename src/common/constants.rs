@@ -1,2 +1,13 @@
 pub const ARENA_WIDTH: u32 = 20;
 pub const ARENA_HEIGHT: u32 = 20;
+
+// Ranges validate_configs clamps the config resources into on startup
+pub const MIN_ARENA_SIZE: i32 = 3;
+pub const MAX_ARENA_SIZE: i32 = 200;
+pub const MIN_INTERPOLATION_BUFFER_MS: f32 = 0.0;
+pub const MAX_INTERPOLATION_BUFFER_MS: f32 = 1000.0;
+pub const MIN_TICK_RATE_HZ: f32 = 1.0;
+pub const MAX_TICK_RATE_HZ: f32 = 60.0;
+pub const MIN_CELL_SIZE: f32 = 1.0;
+pub const MAX_CELL_SIZE: f32 = 500.0;
+pub const MAX_OPPONENTS: u8 = 7;
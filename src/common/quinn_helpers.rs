@@ -61,7 +61,7 @@ pub fn make_server_endpoint(bind_addr: SocketAddr) -> Result<(Endpoint, Vec<u8>)
 /// ## Args
 ///
 /// - server_certs: a list of trusted certificates in DER format.
-fn configure_client(server_certs: &[&[u8]]) -> Result<ClientConfig, Box<dyn Error>> {
+fn configure_client(_server_certs: &[&[u8]]) -> Result<ClientConfig, Box<dyn Error>> {
     // let mut certs = rustls::RootCertStore::empty();
     // for cert in server_certs {
     //     certs.add(&rustls::Certificate(cert.to_vec()))?;
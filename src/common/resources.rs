@@ -0,0 +1,335 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use bevy::prelude::{Color, KeyCode, Timer};
+use bevy::utils::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::common::components::Position;
+use crate::common::constants::{ARENA_HEIGHT, ARENA_WIDTH};
+
+/// Controls how rendered positions are smoothed between grid moves
+pub struct InterpolationConfig {
+    /// Time in milliseconds to glide from the previous grid cell to the current one. Keep this at one movement tick,
+    /// see [`crate::snake::resources::ClientTickRate`].
+    pub buffer_ms: f32,
+}
+
+impl Default for InterpolationConfig {
+    fn default() -> Self {
+        // Matches the default ClientTickRate of 5hz so segments are always in motion
+        Self { buffer_ms: 200.0 }
+    }
+}
+
+/// What happens when a snake reaches the edge of the arena
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArenaMode {
+    /// Snakes come out the opposite edge
+    Wrap,
+    /// The outermost ring of cells is a wall, and running into it kills the snake
+    SolidWalls,
+}
+
+/// Size of the playfield in grid cells, and how its edges behave
+#[derive(Clone, Copy)]
+pub struct ArenaConfig {
+    pub width: i32,
+    pub height: i32,
+    pub mode: ArenaMode,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        Self {
+            width: ARENA_WIDTH as i32,
+            height: ARENA_HEIGHT as i32,
+            mode: ArenaMode::Wrap,
+        }
+    }
+}
+
+/// Walls placed inside the arena at the start of each game, in either [`ArenaMode`]
+#[derive(Default)]
+pub struct ObstacleConfig {
+    pub positions: Vec<Position>,
+}
+
+/// How big the grid is drawn on screen
+pub struct RenderConfig {
+    /// Distance in pixels between the centers of neighbouring cells
+    pub cell_size: f32,
+    /// Pixels trimmed off every sprite, leaving a gap between entities in neighbouring cells
+    pub gap: f32,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        // Fills the default 1000x1000 window with the default 20x20 arena
+        Self {
+            cell_size: 50.0,
+            gap: 0.0,
+        }
+    }
+}
+
+/// Colors the arena is drawn in. Saved with the player's settings, and cycled through the built-in themes with T
+/// during a game.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub background_color: Color,
+    pub wall_color: Color,
+    /// Lines drawn while [`ShowGrid`] is inserted
+    pub grid_color: Color,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        background_color: Color::rgb(0.04, 0.04, 0.04),
+        wall_color: Color::rgb(0.5, 0.25, 0.1),
+        grid_color: Color::rgba(1.0, 1.0, 1.0, 0.15),
+    };
+    pub const LIGHT: Theme = Theme {
+        background_color: Color::rgb(0.92, 0.92, 0.9),
+        wall_color: Color::rgb(0.35, 0.35, 0.4),
+        grid_color: Color::rgba(0.0, 0.0, 0.0, 0.15),
+    };
+    /// Greens of an old handheld's screen
+    pub const RETRO: Theme = Theme {
+        background_color: Color::rgb(0.61, 0.74, 0.06),
+        wall_color: Color::rgb(0.19, 0.38, 0.19),
+        grid_color: Color::rgba(0.06, 0.22, 0.06, 0.25),
+    };
+    pub const BUILT_IN: [Theme; 3] = [Theme::DARK, Theme::LIGHT, Theme::RETRO];
+
+    /// The built-in theme after this one, or the first if this is a custom theme
+    pub fn next(&self) -> Theme {
+        let index = Self::BUILT_IN.iter().position(|theme| theme == self).map_or(0, |i| i + 1);
+        Self::BUILT_IN[index % Self::BUILT_IN.len()]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// Insert to draw the grid's cell boundaries over the arena, e.g. to check things land on cell centers. Toggled
+/// with G during a game.
+pub struct ShowGrid;
+
+/// Insert to draw a faint ghost of the local snake's head where it will move next tick, e.g. to see which way a
+/// queued turn goes. Toggled with V during a game.
+pub struct ShowMovePreview;
+
+/// Source of all gameplay randomness, e.g. food spawns and the random bot. Each game restarts the sequence from
+/// `seed`, so games with the same seed and the same moves play out the same way.
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Starts the sequence over from the seed
+    pub fn reseed(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+/// Whether the camera tracks the local snake, and how quickly it catches up
+pub struct CameraFollowConfig {
+    pub enabled: bool,
+    /// Fraction of the remaining distance covered per second, higher is snappier
+    pub smoothing: f32,
+    /// What the camera watches instead of the local snake while there is a [`Spectator`]
+    pub spectator_target: SpectatorTarget,
+}
+
+impl Default for CameraFollowConfig {
+    fn default() -> Self {
+        // The arena fills the window by default, so there is nothing to follow
+        Self {
+            enabled: false,
+            smoothing: 5.0,
+            spectator_target: SpectatorTarget::Leader,
+        }
+    }
+}
+
+/// Seconds counted down before a game starts, or 0 to start straight away
+pub struct CountdownConfig {
+    pub secs: u8,
+}
+
+impl Default for CountdownConfig {
+    fn default() -> Self {
+        Self { secs: 3 }
+    }
+}
+
+/// Seconds left before the snakes start moving, while in [`crate::state::GameState::Countdown`]
+pub struct Countdown {
+    pub remaining: u8,
+    pub timer: Timer,
+}
+
+impl Countdown {
+    pub fn new(secs: u8) -> Self {
+        Self {
+            remaining: secs,
+            timer: Timer::from_seconds(1.0, true),
+        }
+    }
+}
+
+/// Limits and feel of zooming the camera in and out during a game
+pub struct ZoomConfig {
+    /// Closest the camera zooms in, as a fraction of the default view
+    pub min: f32,
+    /// Furthest the camera zooms out, as a multiple of the default view
+    pub max: f32,
+    /// Fraction the zoom changes by per mouse wheel notch or key press
+    pub step: f32,
+    /// Fraction of the remaining zoom covered per second, higher is snappier
+    pub smoothing: f32,
+}
+
+impl Default for ZoomConfig {
+    fn default() -> Self {
+        Self {
+            min: 0.25,
+            max: 4.0,
+            step: 0.1,
+            smoothing: 10.0,
+        }
+    }
+}
+
+/// Zoom the camera is easing towards, where 1 is the default view and larger is further out. Saved with the
+/// player's settings.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct CameraZoom(pub f32);
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Snake a spectator's camera follows
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpectatorTarget {
+    /// Whichever snake has the longest tail, panning over whenever the lead changes
+    Leader,
+    /// The whole arena, without following anyone
+    Fixed,
+    SnakeId(u8),
+}
+
+impl FromStr for SpectatorTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leader" => Ok(Self::Leader),
+            "fixed" => Ok(Self::Fixed),
+            _ => s.parse().map(Self::SnakeId).map_err(|_| {
+                format!(
+                    "Unknown spectator target '{}', expected one of: leader, fixed, or a snake id",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+/// Insert to watch the game rather than follow the local snake, see [`CameraFollowConfig::spectator_target`]. The
+/// player's keyboard and gamepad no longer steer, though a bot still can.
+pub struct Spectator;
+
+/// Keys a spectator watching a particular snake uses to switch to the next or previous one
+pub struct SpectatorKeybinds {
+    pub next: KeyCode,
+    pub previous: KeyCode,
+}
+
+impl Default for SpectatorKeybinds {
+    fn default() -> Self {
+        Self {
+            next: KeyCode::Right,
+            previous: KeyCode::Left,
+        }
+    }
+}
+
+/// Rate limits warnings that could otherwise repeat every frame or move, e.g. while a snake stays broken
+pub struct LogThrottle {
+    /// Shortest time between two messages with the same key
+    pub interval: Duration,
+    last_logged: HashMap<&'static str, Duration>,
+}
+
+impl LogThrottle {
+    /// Whether a message with this key may be logged at `now`, the time since startup. Remembers it as logged if so.
+    ///
+    /// ```ignore
+    /// if throttle.allow("lost_tail", time.time_since_startup()) {
+    ///     warn!("Snake lost a tail segment");
+    /// }
+    /// ```
+    pub fn allow(&mut self, key: &'static str, now: Duration) -> bool {
+        match self.last_logged.get(key) {
+            Some(last) if now.saturating_sub(*last) < self.interval => false,
+            _ => {
+                self.last_logged.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
+impl Default for LogThrottle {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            last_logged: HashMap::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_allows_each_key_once_per_interval() {
+        let mut throttle = LogThrottle::default();
+        let start = Duration::from_secs(10);
+        assert!(throttle.allow("a", start));
+        assert!(!throttle.allow("a", start));
+        assert!(!throttle.allow("a", start + Duration::from_millis(999)));
+        // Keys are throttled separately
+        assert!(throttle.allow("b", start + Duration::from_millis(500)));
+        assert!(throttle.allow("a", start + Duration::from_secs(1)));
+        // The window restarts from the last message that was allowed, not the first
+        assert!(!throttle.allow("a", start + Duration::from_millis(1500)));
+        assert!(throttle.allow("a", start + Duration::from_secs(2)));
+    }
+}
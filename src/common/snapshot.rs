@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::common::components::Position;
+use crate::food::components::{Food, FoodType};
+use crate::snake::components::SnakeHead;
+use crate::snake::resources::{MovementTick, Scoreboard};
+use crate::state::GameState;
+
+/// Everything needed to know where a game stands, copied out of the ECS world by [`build_snapshot`]
+pub struct GameSnapshot {
+    pub state: GameState,
+    /// Movement ticks since the game started
+    pub tick: u64,
+    /// Live snakes, by id
+    pub snakes: Vec<SnakeSnapshot>,
+    pub food: Vec<FoodSnapshot>,
+}
+
+pub struct SnakeSnapshot {
+    pub id: u8,
+    pub head: Position,
+    /// Tail segments, starting from the one behind the head
+    pub tail: Vec<Position>,
+    pub score: u32,
+}
+
+pub struct FoodSnapshot {
+    pub position: Position,
+    pub food_type: FoodType,
+}
+
+/// Copies the current game out of `world`, e.g. for external tooling or a bot trained outside the game
+pub fn build_snapshot(world: &mut World) -> GameSnapshot {
+    let mut heads = world.query::<(&SnakeHead, &Position)>();
+    let mut snakes: Vec<SnakeSnapshot> = heads
+        .iter(world)
+        .map(|(head, position)| SnakeSnapshot {
+            id: head.id,
+            head: *position,
+            tail: head.tail.iter().filter_map(|tail| world.get::<Position>(*tail).copied()).collect(),
+            score: world
+                .get_resource::<Scoreboard>()
+                .and_then(|scoreboard| scoreboard.scores.get(&head.id).copied())
+                .unwrap_or(0),
+        })
+        .collect();
+    snakes.sort_unstable_by_key(|snake| snake.id);
+
+    let mut foods = world.query::<(&Food, &Position)>();
+    let food = foods
+        .iter(world)
+        .map(|(food, position)| FoodSnapshot {
+            position: *position,
+            food_type: food.food_type,
+        })
+        .collect();
+
+    GameSnapshot {
+        state: world.resource::<CurrentState<GameState>>().0,
+        tick: world.get_resource::<MovementTick>().map_or(0, |tick| tick.0),
+        snakes,
+        food,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::components::Direction;
+
+    fn spawn_snake(world: &mut World, id: u8, head: Position, tail: &[Position]) {
+        let tail = tail.iter().map(|position| world.spawn().insert(*position).id()).collect();
+        world
+            .spawn()
+            .insert(SnakeHead {
+                id,
+                color: Color::WHITE,
+                input_direction: Direction::Up,
+                direction: Direction::Up,
+                tail,
+                speed: 1.0,
+                progress: 0.0,
+            })
+            .insert(head);
+    }
+
+    #[test]
+    fn snapshot_copies_snakes_and_food() {
+        let mut world = World::new();
+        world.insert_resource(CurrentState(GameState::Running));
+        world.insert_resource(MovementTick(7));
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.scores.insert(2, 3);
+        world.insert_resource(scoreboard);
+        spawn_snake(
+            &mut world,
+            2,
+            Position { x: 5, y: 5 },
+            &[Position { x: 5, y: 4 }, Position { x: 5, y: 3 }],
+        );
+        spawn_snake(&mut world, 0, Position { x: 1, y: 1 }, &[]);
+        world
+            .spawn()
+            .insert(Food {
+                food_type: FoodType::Bonus,
+            })
+            .insert(Position { x: 8, y: 2 });
+
+        let snapshot = build_snapshot(&mut world);
+
+        assert_eq!(snapshot.state, GameState::Running);
+        assert_eq!(snapshot.tick, 7);
+        let ids: Vec<_> = snapshot.snakes.iter().map(|snake| snake.id).collect();
+        assert_eq!(ids, [0, 2]);
+        assert_eq!(snapshot.snakes[0].head, Position { x: 1, y: 1 });
+        assert!(snapshot.snakes[0].tail.is_empty());
+        assert_eq!(snapshot.snakes[0].score, 0);
+        assert_eq!(snapshot.snakes[1].head, Position { x: 5, y: 5 });
+        assert_eq!(
+            snapshot.snakes[1].tail,
+            [Position { x: 5, y: 4 }, Position { x: 5, y: 3 }]
+        );
+        assert_eq!(snapshot.snakes[1].score, 3);
+        assert_eq!(snapshot.food.len(), 1);
+        assert_eq!(snapshot.food[0].position, Position { x: 8, y: 2 });
+        assert_eq!(snapshot.food[0].food_type, FoodType::Bonus);
+    }
+}
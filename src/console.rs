@@ -0,0 +1,128 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+
+use crate::console::commands::parse;
+use crate::console::resources::{ConsoleCommands, ConsoleState};
+use crate::snake::components::SnakeState;
+
+mod commands;
+pub mod resources;
+
+const CONSOLE_KEY: KeyCode = KeyCode::Grave;
+const CONSOLE_FONT_SIZE: f32 = 20.0;
+
+/// In-game console for poking at the game while testing, opened with the key left of 1. Type `help` to list the
+/// commands, and register more on [`ConsoleCommands`].
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        let mut console_commands = ConsoleCommands::default();
+        console_commands
+            .register("help", commands::help)
+            .register("spawn_food", commands::spawn_food)
+            .register("set_tickrate", commands::set_tickrate)
+            .register("toggle", commands::toggle);
+        app.insert_resource(console_commands)
+            .init_resource::<ConsoleState>()
+            .add_startup_system(console_setup)
+            // Straight after input is read, so typed keys can be hidden from the rest of the game
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                console_input.after(InputSystem).before(SnakeState::Input),
+            )
+            .add_system(update_console_ui);
+    }
+}
+
+// Tag component for the console's text
+#[derive(Component)]
+struct ConsoleText;
+
+fn console_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: CONSOLE_FONT_SIZE,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(ConsoleText);
+}
+
+// Types into the console while it is open, running the line on enter
+fn console_input(
+    mut commands: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut wheel: ResMut<Events<MouseWheel>>,
+    mut chars: EventReader<ReceivedCharacter>,
+    console_commands: Res<ConsoleCommands>,
+    mut console: ResMut<ConsoleState>,
+) {
+    if keys.just_pressed(CONSOLE_KEY) {
+        console.open = !console.open;
+        console.line.clear();
+        keys.reset_all();
+        chars.clear();
+        return;
+    }
+    if !console.open {
+        return;
+    }
+
+    for c in chars.iter().map(|event| event.char) {
+        match c {
+            '\r' | '\n' => {
+                let line = std::mem::take(&mut console.line);
+                let (name, args) = match parse(&line) {
+                    Some(parsed) => parsed,
+                    None => continue,
+                };
+                match console_commands.get(name) {
+                    Ok(command) => {
+                        commands.add(move |world: &mut World| {
+                            let output = command(world, &args).unwrap_or_else(|e| e);
+                            info!("Console: {}", output);
+                            world.resource_mut::<ConsoleState>().output = output;
+                        });
+                    }
+                    Err(e) => console.output = e,
+                }
+            }
+            '\u{8}' => {
+                console.line.pop();
+            }
+            c if !c.is_control() => console.line.push(c),
+            _ => {}
+        }
+    }
+    // Keep what is typed from also steering the snake or toggling overlays, and the camera from zooming. clear only
+    // drops this frame's presses, so held keys would still count as pressed without reset_all.
+    keys.reset_all();
+    wheel.clear();
+}
+
+fn update_console_ui(console: Res<ConsoleState>, mut texts: Query<(&mut Text, &mut Visibility), With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in texts.iter_mut() {
+        visibility.is_visible = console.open;
+        text.sections[0].value = format!("{}\n> {}", console.output, console.line);
+    }
+}
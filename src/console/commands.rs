@@ -0,0 +1,164 @@
+use std::str::FromStr;
+
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+
+use crate::common::constants::{MAX_TICK_RATE_HZ, MIN_TICK_RATE_HZ};
+use crate::common::resources::{ArenaConfig, GameRng, ShowGrid, ShowMovePreview};
+use crate::console::resources::ConsoleCommands;
+use crate::food::components::FoodType;
+use crate::food::{random_position, spawn_food_at};
+use crate::snake::resources::ClientTickRate;
+
+/// Splits a typed line into the command name and its arguments, or None if nothing was typed
+pub fn parse(line: &str) -> Option<(&str, Vec<String>)> {
+    let mut words = line.split_whitespace();
+    let name = words.next()?;
+    Some((name, words.map(String::from).collect()))
+}
+
+// Argument `i`, parsed as whatever the command expects
+fn arg<T: FromStr>(args: &[String], i: usize, name: &str) -> Result<T, String> {
+    let arg = args.get(i).ok_or_else(|| format!("Missing argument <{}>", name))?;
+    arg.parse().map_err(|_| format!("Invalid <{}>: '{}'", name, arg))
+}
+
+// Fails if more than `max` arguments were typed, rather than quietly ignoring the extra ones
+fn max_args(args: &[String], max: usize) -> Result<(), String> {
+    if args.len() > max {
+        return Err(format!("Too many arguments, expected at most {}", max));
+    }
+    Ok(())
+}
+
+pub fn help(world: &mut World, args: &[String]) -> Result<String, String> {
+    max_args(args, 0)?;
+    let mut names: Vec<_> = world.resource::<ConsoleCommands>().commands.keys().copied().collect();
+    names.sort_unstable();
+    Ok(names.join(", "))
+}
+
+/// `spawn_food [normal|bonus|shrink|boost]`, dropping food on a random cell
+pub fn spawn_food(world: &mut World, args: &[String]) -> Result<String, String> {
+    max_args(args, 1)?;
+    let food_type = match args.first().map(String::as_str) {
+        None | Some("normal") => FoodType::Normal,
+        Some("bonus") => FoodType::Bonus,
+        Some("shrink") => FoodType::Shrink,
+        Some("boost") => FoodType::Boost,
+        Some(other) => {
+            return Err(format!(
+                "Unknown food type '{}', expected one of: normal, bonus, shrink, boost",
+                other
+            ))
+        }
+    };
+    let mut state: SystemState<(Commands, Res<ArenaConfig>, ResMut<GameRng>)> = SystemState::new(world);
+    let (mut commands, arena, mut rng) = state.get_mut(world);
+    let position = random_position(&arena, &mut rng.rng);
+    spawn_food_at(&mut commands, position, food_type);
+    state.apply(world);
+    Ok(format!(
+        "Spawned {:?} food at {}, {}",
+        food_type, position.x, position.y
+    ))
+}
+
+/// `set_tickrate <hz>`, changing how many times a second snakes move
+pub fn set_tickrate(world: &mut World, args: &[String]) -> Result<String, String> {
+    max_args(args, 1)?;
+    let hz: f32 = arg(args, 0, "hz")?;
+    // "inf" and "NaN" parse as floats too
+    if !hz.is_finite() {
+        return Err(format!("Invalid <hz>: '{}'", hz));
+    }
+    let hz = hz.clamp(MIN_TICK_RATE_HZ, MAX_TICK_RATE_HZ);
+    world.insert_resource(ClientTickRate { hz });
+    Ok(format!("Tick rate set to {} hz", hz))
+}
+
+/// `toggle <grid|preview>`, showing or hiding an overlay
+pub fn toggle(world: &mut World, args: &[String]) -> Result<String, String> {
+    max_args(args, 1)?;
+    let shown = match arg::<String>(args, 0, "overlay")?.as_str() {
+        "grid" => toggle_resource(world, ShowGrid),
+        "preview" => toggle_resource(world, ShowMovePreview),
+        other => return Err(format!("Unknown overlay '{}', expected one of: grid, preview", other)),
+    };
+    Ok(if shown { "Shown".to_string() } else { "Hidden".to_string() })
+}
+
+// Inserts the resource if it is missing and removes it otherwise, returning whether it is now there
+fn toggle_resource<R: Send + Sync + 'static>(world: &mut World, resource: R) -> bool {
+    if world.remove_resource::<R>().is_some() {
+        false
+    } else {
+        world.insert_resource(resource);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parse_splits_name_from_args() {
+        assert_eq!(parse("  toggle   grid "), Some(("toggle", words("grid"))));
+        assert_eq!(parse("help"), Some(("help", vec![])));
+        assert_eq!(parse("   "), None);
+    }
+
+    #[test]
+    fn arg_parses_or_explains_why_not() {
+        let args = words("5 fast");
+        assert_eq!(arg::<f32>(&args, 0, "hz"), Ok(5.0));
+        assert_eq!(arg::<f32>(&args, 1, "hz"), Err("Invalid <hz>: 'fast'".to_string()));
+        assert_eq!(arg::<f32>(&args, 2, "hz"), Err("Missing argument <hz>".to_string()));
+    }
+
+    #[test]
+    fn unknown_command_is_reported() {
+        let mut console_commands = ConsoleCommands::default();
+        console_commands.register("help", help);
+        assert!(console_commands.get("help").is_ok());
+        assert_eq!(
+            console_commands.get("halp").err(),
+            Some("Unknown command 'halp', try help".to_string())
+        );
+    }
+
+    #[test]
+    fn wrong_number_of_args_is_rejected() {
+        let mut world = World::new();
+        world.insert_resource(ConsoleCommands::default());
+        assert_eq!(
+            help(&mut world, &words("me")),
+            Err("Too many arguments, expected at most 0".to_string())
+        );
+        assert_eq!(set_tickrate(&mut world, &[]), Err("Missing argument <hz>".to_string()));
+        assert_eq!(
+            set_tickrate(&mut world, &words("5 6")),
+            Err("Too many arguments, expected at most 1".to_string())
+        );
+        assert_eq!(toggle(&mut world, &[]), Err("Missing argument <overlay>".to_string()));
+        assert!(world.get_resource::<ClientTickRate>().is_none());
+    }
+
+    #[test]
+    fn set_tickrate_rejects_non_finite_and_clamps() {
+        let mut world = World::new();
+        assert!(set_tickrate(&mut world, &words("inf")).is_err());
+        assert!(set_tickrate(&mut world, &words("NaN")).is_err());
+        assert!(world.get_resource::<ClientTickRate>().is_none());
+
+        set_tickrate(&mut world, &words("1000")).unwrap();
+        assert_eq!(world.resource::<ClientTickRate>().hz, MAX_TICK_RATE_HZ);
+        set_tickrate(&mut world, &words("0")).unwrap();
+        assert_eq!(world.resource::<ClientTickRate>().hz, MIN_TICK_RATE_HZ);
+    }
+}
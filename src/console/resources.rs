@@ -0,0 +1,31 @@
+use bevy::prelude::World;
+use bevy::utils::HashMap;
+
+/// A console command, run with the words typed after its name. Returns what to print back, or why it failed.
+pub type ConsoleCommand = fn(&mut World, &[String]) -> Result<String, String>;
+
+/// Commands the console knows, by the name typed to run them. Register more with [`ConsoleCommands::register`].
+#[derive(Default)]
+pub struct ConsoleCommands {
+    pub commands: HashMap<&'static str, ConsoleCommand>,
+}
+
+impl ConsoleCommands {
+    pub fn register(&mut self, name: &'static str, command: ConsoleCommand) -> &mut Self {
+        self.commands.insert(name, command);
+        self
+    }
+
+    /// The command typed as `name`, or what to print back if there is none
+    pub fn get(&self, name: &str) -> Result<ConsoleCommand, String> {
+        self.commands.get(name).copied().ok_or_else(|| format!("Unknown command '{}', try help", name))
+    }
+}
+
+/// Whether the console is showing, what is being typed into it, and the reply to the last command
+#[derive(Default)]
+pub struct ConsoleState {
+    pub open: bool,
+    pub line: String,
+    pub output: String,
+}
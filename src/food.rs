@@ -1,75 +1,216 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use iyes_loopless::prelude::*;
-use rand::random;
+use rand::Rng;
 
-use crate::common::components::Position;
 use crate::common::components::Size;
-use crate::common::constants::{ARENA_HEIGHT, ARENA_WIDTH};
-use crate::food::components::Food;
-use crate::snake::components::{SnakeHead, SnakeState};
-use crate::snake::spawn_tail;
+use crate::common::components::{Position, PositionState, Wall};
+use crate::common::resources::{ArenaConfig, ArenaMode, GameRng};
+use crate::food::components::{Food, FoodState, FoodType};
+use crate::food::events::{FoodEatenEvent, StreakEvent};
+use crate::snake::components::{EatStreak, SnakeHead, SnakeState, SpeedBoost};
+use crate::snake::events::{GameAudioEvent, RemoveTailEvent, SnakeDeathEvent};
+use crate::snake::resources::{Scoreboard, SnakeRegistry, StreakConfig, TailGradientConfig, TailLengthConfig};
+use crate::snake::{spawn_tail, tail_color, MOVEMENT_TIMESTEP};
 use crate::state::GameState;
 
 pub mod components;
+pub mod events;
 
 pub struct FoodPlugin;
 
 impl Plugin for FoodPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(eat_food.run_in_state(GameState::Running).after(SnakeState::Movement))
+        app.add_event::<FoodEatenEvent>()
+            .add_event::<StreakEvent>()
+            .init_resource::<StreakConfig>()
+            // Straight after every movement step, so a snake moving more than once in a frame can't pass over food.
+            // After collisions too, so a snake that died on this step doesn't also eat.
+            .add_fixed_timestep_system(
+                MOVEMENT_TIMESTEP,
+                0,
+                eat_food
+                    .run_in_state(GameState::Running)
+                    .label(FoodState::Eating)
+                    .after(SnakeState::Movement)
+                    .after(SnakeState::Collision),
+            )
+            .add_system(handle_food_eaten.run_in_state(GameState::Running))
+            .add_system(track_eat_streaks.run_in_state(GameState::Running))
             .add_fixed_timestep(Duration::from_secs(1), "spawn_food")
-            .add_fixed_timestep_system("spawn_food", 0, spawn_food.run_in_state(GameState::Running));
+            .add_fixed_timestep_system("spawn_food", 0, spawn_food.run_in_state(GameState::Running))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                pulse_bonus_food.run_in_state(GameState::Running).after(PositionState::Translation),
+            );
     }
 }
 
-const FOOD_COLOR: Color = Color::rgb(1.0, 0.0, 1.0);
+// Chances that a newly spawned food is a bonus or shrink one, otherwise it is normal
+const BONUS_FOOD_CHANCE: f32 = 0.1;
+const SHRINK_FOOD_CHANCE: f32 = 0.1;
+const BOOST_FOOD_CHANCE: f32 = 0.05;
+// Tail segments lost by eating shrink food
+const SHRINK_SEGMENTS: usize = 2;
+// Speed, in cells per tick, that boost food raises a snake to, and for how many seconds
+const BOOST_SPEED: f32 = 2.0;
+const BOOST_SECS: f32 = 3.0;
+// How much bonus food grows and shrinks by, and how many times a second it pulses
+const BONUS_PULSE_SCALE: f32 = 0.15;
+const BONUS_PULSE_HZ: f32 = 2.0;
 
-fn spawn_food(mut commands: Commands) {
+fn spawn_food(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    mut rng: ResMut<GameRng>,
+    walls: Query<&Position, With<Wall>>,
+) {
+    let position = random_position(&arena, &mut rng.rng);
+    // Food inside an obstacle could never be eaten, so skip it and wait for the next spawn instead
+    if walls.iter().any(|wall| *wall == position) {
+        return;
+    }
+    let roll = rng.rng.gen::<f32>();
+    let food_type = if roll < BONUS_FOOD_CHANCE {
+        FoodType::Bonus
+    } else if roll < BONUS_FOOD_CHANCE + SHRINK_FOOD_CHANCE {
+        FoodType::Shrink
+    } else if roll < BONUS_FOOD_CHANCE + SHRINK_FOOD_CHANCE + BOOST_FOOD_CHANCE {
+        FoodType::Boost
+    } else {
+        FoodType::Normal
+    };
+    spawn_food_at(&mut commands, position, food_type);
+}
+
+pub fn spawn_food_at(commands: &mut Commands, position: Position, food_type: FoodType) {
     commands
         .spawn_bundle(SpriteBundle {
             sprite: Sprite {
-                color: FOOD_COLOR,
+                color: food_type.color(),
                 ..default()
             },
             ..default()
         })
-        .insert(Food)
-        .insert(Position {
-            x: (random::<f32>() * ARENA_WIDTH as f32) as i32,
-            y: (random::<f32>() * ARENA_HEIGHT as f32) as i32,
-        })
+        .insert(Food { food_type })
+        .insert(position)
         .insert(Size::square(0.8));
 }
 
+/// Random cell a snake can reach, which excludes the border when it is a wall
+pub fn random_position(arena: &ArenaConfig, rng: &mut impl Rng) -> Position {
+    let inset = if arena.mode == ArenaMode::SolidWalls { 1 } else { 0 };
+    Position {
+        x: rng.gen_range(inset..arena.width - inset),
+        y: rng.gen_range(inset..arena.height - inset),
+    }
+}
+
+// Grows or shrinks whichever live snake is on a food, leaving scoring to handle_food_eaten
+#[allow(clippy::too_many_arguments)]
 fn eat_food(
     mut commands: Commands,
-    foods: Query<(Entity, &Position), With<Food>>,
-    mut snakes: Query<(&Position, &mut SnakeHead)>,
+    mut death_events: EventReader<SnakeDeathEvent>,
+    foods: Query<(Entity, &Position, &Food)>,
+    tail_gradient: Res<TailGradientConfig>,
+    tail_length: Res<TailLengthConfig>,
+    mut remove_tail_events: EventWriter<RemoveTailEvent>,
+    mut food_eaten_events: EventWriter<FoodEatenEvent>,
+    mut snakes: Query<(Entity, &Position, &mut SnakeHead)>,
     positions: Query<&Position, (Without<SnakeHead>, Without<Food>)>,
 ) {
     let food_positions = get_food_positions(foods);
+    // Snakes that died on this step keep their head until the step's commands are applied
+    let dead: HashSet<u8> = death_events.iter().map(|event| event.id).collect();
 
-    for (position, mut head) in snakes.iter_mut() {
-        if let Some(entity) = food_positions.get(position) {
+    for (snake, position, mut head) in snakes.iter_mut().filter(|(_, _, head)| !dead.contains(&head.id)) {
+        if let Some((entity, food_type)) = food_positions.get(position) {
             commands.entity(*entity).despawn();
-            let mut position = position;
-            if !head.tail.is_empty() {
-                position = positions.get(*head.tail.last().unwrap()).unwrap();
+            if *food_type == FoodType::Shrink {
+                remove_tail_events.send(RemoveTailEvent {
+                    id: head.id,
+                    count: SHRINK_SEGMENTS,
+                });
+            } else if tail_length.max_length.is_none_or(|max_length| head.tail.len() < max_length) {
+                // New segment starts on top of the last one, or the head if there is no tail yet
+                let position = head.tail.last().and_then(|tail| positions.get(*tail).ok()).unwrap_or(position);
+                let color = tail_color(head.color, head.tail.len(), &tail_gradient);
+                head.tail.push(spawn_tail(&mut commands, *position, color));
             }
-            head.tail.push(spawn_tail(&mut commands, *position));
+            if *food_type == FoodType::Boost {
+                // Eating another while boosted starts the boost over
+                head.speed = BOOST_SPEED;
+                commands.entity(snake).insert(SpeedBoost {
+                    timer: Timer::from_seconds(BOOST_SECS, false),
+                });
+            }
+            food_eaten_events.send(FoodEatenEvent {
+                snake_id: head.id,
+                food_type: *food_type,
+            });
         }
     }
 }
 
+fn handle_food_eaten(
+    mut food_eaten_events: EventReader<FoodEatenEvent>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut audio_events: EventWriter<GameAudioEvent>,
+) {
+    for event in food_eaten_events.iter() {
+        *scoreboard.scores.entry(event.snake_id).or_default() += event.food_type.points();
+        audio_events.send(GameAudioEvent::AteFood);
+    }
+}
+
+// Extends the eater's streak if it ate again within the window, or starts a new one otherwise
+fn track_eat_streaks(
+    time: Res<Time>,
+    config: Res<StreakConfig>,
+    registry: Res<SnakeRegistry>,
+    mut food_eaten_events: EventReader<FoodEatenEvent>,
+    mut streak_events: EventWriter<StreakEvent>,
+    mut streaks: Query<&mut EatStreak>,
+) {
+    let now = time.seconds_since_startup();
+    for event in food_eaten_events.iter() {
+        let mut streak = match registry.snakes.get(&event.snake_id).and_then(|head| streaks.get_mut(*head).ok()) {
+            Some(streak) => streak,
+            None => continue,
+        };
+        if streak.count > 0 && now - streak.last_eat_time <= config.window_secs as f64 {
+            streak.count += 1;
+            streak_events.send(StreakEvent {
+                id: event.snake_id,
+                multiplier: streak.count,
+            });
+        } else {
+            streak.count = 1;
+        }
+        streak.last_eat_time = now;
+    }
+}
+
 #[inline]
-fn get_food_positions(foods: Query<(Entity, &Position), With<Food>>) -> HashMap<Position, Entity> {
+fn get_food_positions(foods: Query<(Entity, &Position, &Food)>) -> HashMap<Position, (Entity, FoodType)> {
     let mut food_positions = HashMap::new();
     // Assumes no position has multiple food
-    for (entity, position) in foods.iter() {
-        food_positions.insert(*position, entity);
+    for (entity, position, food) in foods.iter() {
+        food_positions.insert(*position, (entity, food.food_type));
     }
     food_positions
 }
+
+// Grows and shrinks bonus food on top of the scale size_scaling gives it
+fn pulse_bonus_food(time: Res<Time>, mut foods: Query<(&Food, &mut Transform)>) {
+    let pulse =
+        1. + BONUS_PULSE_SCALE * (time.seconds_since_startup() as f32 * BONUS_PULSE_HZ * std::f32::consts::TAU).sin();
+    for (food, mut transform) in foods.iter_mut() {
+        if food.food_type == FoodType::Bonus {
+            transform.scale.x *= pulse;
+            transform.scale.y *= pulse;
+        }
+    }
+}
@@ -1,4 +1,44 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Color, Component, SystemLabel};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum FoodState {
+    Eating,
+}
 
 #[derive(Component)]
-pub struct Food;
+pub struct Food {
+    pub food_type: FoodType,
+}
+
+/// Kind of food, which decides how it looks and what eating it does
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FoodType {
+    Normal,
+    /// Worth extra points, and pulses so it stands out
+    Bonus,
+    /// Takes segments off the tail instead of adding one
+    Shrink,
+    /// Speeds the snake up for a few seconds, on top of growing it
+    Boost,
+}
+
+impl FoodType {
+    /// Points scored for eating this food
+    pub fn points(self) -> u32 {
+        match self {
+            FoodType::Normal => 1,
+            FoodType::Bonus => 3,
+            FoodType::Shrink => 1,
+            FoodType::Boost => 1,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            FoodType::Normal => Color::rgb(1.0, 0.0, 1.0),
+            FoodType::Bonus => Color::rgb(1.0, 0.84, 0.0),
+            FoodType::Shrink => Color::rgb(0.0, 0.8, 1.0),
+            FoodType::Boost => Color::rgb(1.0, 0.4, 0.0),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::food::components::FoodType;
+
+/// Fired when a snake eats food, after its tail has grown or shrunk. Scoring and anything else that cares about
+/// what was eaten goes off this, so it does not have to be inferred from the tail changing.
+pub struct FoodEatenEvent {
+    pub snake_id: u8,
+    pub food_type: FoodType,
+}
+
+/// Fired when a snake eats again soon enough after its last food to extend its streak, see
+/// [`crate::snake::resources::StreakConfig`]
+pub struct StreakEvent {
+    pub id: u8,
+    /// Foods eaten in a row, starting from 2
+    pub multiplier: u32,
+}
@@ -1,6 +1,25 @@
+#![allow(clippy::type_complexity)]
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
 use bevy::prelude::*;
+use bevy::time::create_time_channels;
+use iyes_loopless::prelude::*;
+
+use crate::bot::resources::{BotController, BotStrategy, OpponentConfig};
+use crate::common::resources::{CameraFollowConfig, CountdownConfig, GameRng, Spectator, SpectatorTarget};
+use crate::common::snapshot::build_snapshot;
+use crate::snake::observer::LoggingObserver;
+use crate::snake::resources::{ColorblindMode, RespawnConfig};
+use crate::snake::settings::SettingsFile;
+use crate::snake::textures::SnakeTextures;
+use crate::state::GameState;
 
+mod bot;
 mod common;
+#[cfg(feature = "console")]
+mod console;
 mod food;
 mod snake;
 mod state;
@@ -10,31 +29,209 @@ mod ui;
 mod client;
 mod server;
 
+/// Frames to run when started with `--headless`
+const HEADLESS_FRAMES: u32 = 1000;
+/// Game time each headless frame moves the clock on by, as if running at 60 frames a second
+const HEADLESS_FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
 
 #[tokio::main]
 async fn main() {
-    let server = tokio::spawn(async {
+    if std::env::args().any(|arg| arg == "--headless") {
+        run_headless();
+        return;
+    }
+
+    let _server = tokio::spawn(async {
         server::server::run().await.unwrap();
     });
-    let client = tokio::spawn(async {
+    let _client = tokio::spawn(async {
         client::client::run().await.unwrap();
     });
 
     //client::client::run();
-    App::new()
-        .insert_resource(WindowDescriptor {
-            title: "Snake!".to_string(),
-            width: 1000.0,
-            height: 1000.0,
-            // TODO: always opens on primary monitor, can't find the Current monitor for some reason
-            position: WindowPosition::Centered(MonitorSelection::Primary),
+    let mut app = App::new();
+    app.insert_resource(WindowDescriptor {
+        title: "Snake!".to_string(),
+        width: 1000.0,
+        height: 1000.0,
+        // TODO: always opens on primary monitor, can't find the Current monitor for some reason
+        position: WindowPosition::Centered(MonitorSelection::Primary),
+        ..default()
+    })
+    .init_resource::<SettingsFile>()
+    .add_plugins(DefaultPlugins)
+    .add_plugin(common::CommonPlugin)
+    .add_plugin(ui::UiPlugin)
+    .add_plugin(food::FoodPlugin)
+    .add_plugin(snake_plugin())
+    .add_plugin(bot::BotPlugin);
+    #[cfg(feature = "console")]
+    app.add_plugin(console::ConsolePlugin);
+    let bot_strategy = bot_strategy_arg();
+    if let Some(strategy) = bot_strategy {
+        app.insert_resource(BotController { strategy });
+    }
+    if let Some(seed) = seed_arg() {
+        app.insert_resource(GameRng::new(seed));
+    }
+    let opponents = opponents_arg();
+    if let Some(count) = opponents {
+        app.insert_resource(OpponentConfig {
+            count,
+            strategy: bot_strategy.unwrap_or(BotStrategy::AvoidCollision),
+            teams: std::env::args().any(|arg| arg == "--teams"),
+        });
+    }
+    if let Some(target) = spectate_arg() {
+        // Without a bot the local snake sits the game out, so there has to be someone else to watch
+        if bot_strategy.is_some() || opponents.is_some_and(|count| count > 0) {
+            app.insert_resource(Spectator).insert_resource(CameraFollowConfig {
+                spectator_target: target,
+                ..default()
+            });
+        } else {
+            eprintln!("--spectate needs --bot or --opponents so there is a snake to watch, ignoring it");
+        }
+    }
+    if std::env::args().any(|arg| arg == "--respawn") {
+        app.insert_resource(RespawnConfig {
+            enabled: true,
             ..default()
-        })
-        .insert_resource(ClearColor(Color::rgb(0.04, 0.04, 0.04)))
-        .add_plugins(DefaultPlugins)
-        .add_plugin(ui::UiPlugin)
+        });
+    }
+    if let Some(dir) = snake_textures_arg() {
+        let textures = SnakeTextures::load(app.world.resource::<AssetServer>(), &dir);
+        app.insert_resource(textures);
+    }
+    if let Some(mode) = colorblind_arg() {
+        app.insert_resource(mode);
+    }
+    app.run();
+}
+
+/// Runs the game logic without a window or GPU, skipping the menu straight into a game, e.g. for CI
+fn run_headless() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
         .add_plugin(common::CommonPlugin)
         .add_plugin(food::FoodPlugin)
-        .add_plugin(snake::SnakePlugin)
-        .run();
+        .add_plugin(snake_plugin())
+        .add_plugin(bot::BotPlugin)
+        // Let a bot play so the run exercises movement, eating and collisions
+        .insert_resource(BotController {
+            strategy: bot_strategy_arg().unwrap_or(BotStrategy::AvoidCollision),
+        })
+        .insert_resource(CountdownConfig { secs: 0 })
+        .insert_resource(NextState(GameState::PreGame));
+    if let Some(seed) = seed_arg() {
+        app.insert_resource(GameRng::new(seed));
+    }
+    if let Some(count) = opponents_arg() {
+        app.insert_resource(OpponentConfig {
+            count,
+            teams: std::env::args().any(|arg| arg == "--teams"),
+            ..default()
+        });
+    }
+    // Frames run back to back without a window to pace them, so feed the clock simulated time instead of the wall
+    // clock, or next to none would pass and nothing would move
+    let (time_sender, time_receiver) = create_time_channels();
+    app.insert_resource(time_receiver);
+    let start = Instant::now();
+    for frame in 1..=HEADLESS_FRAMES {
+        time_sender.0.send(start + HEADLESS_FRAME_TIME * frame).unwrap();
+        app.update();
+    }
+
+    let snapshot = build_snapshot(&mut app.world);
+    println!("Stopped in {:?} after {} ticks", snapshot.state, snapshot.tick);
+    for snake in snapshot.snakes.iter() {
+        println!(
+            "Snake {} at {:?} with {} tail segments, scoring {}",
+            snake.id,
+            snake.head,
+            snake.tail.len(),
+            snake.score
+        );
+    }
+    for food in snapshot.food.iter() {
+        println!("{:?} food at {:?}", food.food_type, food.position);
+    }
+}
+
+/// Snake plugin, logging every snake's moves when started with `--log-snakes`
+fn snake_plugin() -> snake::SnakePlugin {
+    let plugin = snake::SnakePlugin::default();
+    if std::env::args().any(|arg| arg == "--log-snakes") {
+        plugin.with_observer(LoggingObserver)
+    } else {
+        plugin
+    }
+}
+
+/// Strategy passed as `--bot <random|seek|avoid>` to have a bot play instead of the player
+fn bot_strategy_arg() -> Option<BotStrategy> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--bot").skip(1);
+    match args.next()?.parse() {
+        Ok(strategy) => Some(strategy),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+/// Seed passed as `--seed <n>` to make food spawns and the random bot repeat between runs
+fn seed_arg() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    match args.next()?.parse() {
+        Ok(seed) => Some(seed),
+        Err(e) => {
+            eprintln!("Invalid seed: {}", e);
+            None
+        }
+    }
+}
+
+/// Number of bot opponents passed as `--opponents <n>`, playing with the `--bot` strategy if there is one. Add
+/// `--teams` to split everyone into two teams.
+fn opponents_arg() -> Option<u8> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--opponents").skip(1);
+    match args.next()?.parse() {
+        Ok(count) => Some(count),
+        Err(e) => {
+            eprintln!("Invalid number of opponents: {}", e);
+            None
+        }
+    }
+}
+
+/// Target passed as `--spectate <leader|fixed|id>` to watch the game instead of following the local snake. The
+/// local snake only plays while spectating if `--bot` is playing it.
+fn spectate_arg() -> Option<SpectatorTarget> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--spectate").skip(1);
+    match args.next()?.parse() {
+        Ok(target) => Some(target),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+/// Palette passed as `--colorblind <off|deuteranopia|protanopia|high-contrast>` to draw snakes with
+fn colorblind_arg() -> Option<ColorblindMode> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--colorblind").skip(1);
+    match args.next()?.parse() {
+        Ok(mode) => Some(mode),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+/// Folder in assets passed as `--snake-textures <dir>` to draw snakes with the images in it, see [`SnakeTextures`]
+fn snake_textures_arg() -> Option<PathBuf> {
+    std::env::args().skip_while(|arg| arg != "--snake-textures").nth(1).map(PathBuf::from)
 }
@@ -1 +1,2 @@
+#[allow(clippy::module_inception)]
 pub mod server;
\ No newline at end of file
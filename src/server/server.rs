@@ -14,7 +14,7 @@ use crate::common::quinn_helpers::make_server_endpoint;
 
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let server_addr = "127.0.0.1:5000".parse().unwrap();
-    let (endpoint, server_cert) = make_server_endpoint(server_addr)?;
+    let (endpoint, _server_cert) = make_server_endpoint(server_addr)?;
     
     // Single connection
     let incoming_conn = endpoint.accept().await.unwrap();
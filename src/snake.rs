@@ -1,129 +1,1093 @@
-use std::time::Duration;
+use std::sync::Arc;
 
+use bevy::input::InputSystem;
 use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
 use iyes_loopless::prelude::*;
 
-use crate::common::components::{Direction, Position, Size};
-use crate::snake::components::{SnakeHead, SnakeState, Tail};
+use crate::bot::resources::BotController;
+use crate::common::cell_to_world;
+use crate::common::components::{Direction, InterpolationBuffer, Obstacle, Position, RenderPosition, Size, Wall};
+use crate::common::resources::{
+    ArenaConfig, ArenaMode, CameraZoom, GameRng, LogThrottle, RenderConfig, Spectator, Theme,
+};
+use crate::food::random_position;
+use crate::snake::components::{
+    DeathFade, EatStreak, InputQueue, PredictedCollision, SnakeHead, SnakeName, SnakeState, SpeedBoost, SpeedTrail,
+    Tail, Team,
+};
+use crate::snake::events::{
+    CollisionCause, GameAudioEvent, GameResultEvent, InputRejectedEvent, PredictedCollisionEvent,
+    PredictionCorrectedEvent, RemoveTailEvent, SnakeDeathEvent,
+};
+use crate::snake::observer::{notify_observers, SnakeObserver, SnakeObservers};
+use crate::snake::resources::{
+    ClientTickRate, ColorblindMode, ControlInversion, DeathFadeConfig, GamepadDeadzone, InputBufferConfig,
+    LocalHighlightConfig, LocalSnake, MovementKeybinds, MovementStep, MovementTick, PendingRespawn,
+    PlayerColorPreference, PlayerName, PlayerTeam, RespawnConfig, RespawnQueue, Scoreboard, SnakeRegistry,
+    SnakeSpawnConfig, TailGradientConfig, TailLengthConfig,
+};
+use crate::snake::settings::{save_settings, ClientSettings, SettingsFile};
+use crate::snake::textures::{apply_snake_textures, SnakeTextures};
 use crate::state::GameState;
 
+#[cfg(feature = "body_path")]
+mod bodypath;
 pub mod components;
+#[cfg(feature = "validate_continuity")]
+mod continuity;
+pub mod events;
+pub mod observer;
+pub mod resources;
+pub mod settings;
+pub mod textures;
 
-pub struct SnakePlugin;
+#[derive(Default)]
+pub struct SnakePlugin {
+    observers: Vec<Arc<dyn SnakeObserver>>,
+}
+
+impl SnakePlugin {
+    /// Calls `observer` whenever a snake spawns, moves, grows or dies
+    pub fn with_observer(mut self, observer: impl SnakeObserver) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+}
+
+/// Name of the fixed timestep that snake movement runs on, for looking it up in [`FixedTimesteps`]
+pub const MOVEMENT_TIMESTEP: &str = "snake_movement";
+/// Fastest a snake can move, in cells per tick. Movement steps this many times a tick so that even the fastest snakes
+/// only ever move one cell at a time, and collisions and food are checked after every one of those steps.
+pub const MAX_SPEED: u32 = 2;
+// How long each cell of a boosted snake's trail takes to fade, and how opaque it starts
+const TRAIL_FADE_SECS: f32 = 0.3;
+const TRAIL_ALPHA: f32 = 0.4;
 
 impl Plugin for SnakePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(snake_movement.run_in_state(GameState::Running).label(SnakeState::Movement))
-            .add_system(snake_movement_input.run_in_state(GameState::Running).after(SnakeState::Movement));
+        app.init_resource::<MovementKeybinds>()
+            .init_resource::<GamepadDeadzone>()
+            .init_resource::<ControlInversion>()
+            .init_resource::<TailGradientConfig>()
+            .init_resource::<PlayerColorPreference>()
+            .init_resource::<SnakeSpawnConfig>()
+            .init_resource::<TailLengthConfig>()
+            .init_resource::<PlayerName>()
+            .init_resource::<PlayerTeam>()
+            .init_resource::<ColorblindMode>()
+            .init_resource::<InputBufferConfig>()
+            .init_resource::<RespawnConfig>()
+            .init_resource::<RespawnQueue>()
+            .init_resource::<SnakeRegistry>()
+            .init_resource::<MovementTick>()
+            .init_resource::<MovementStep>()
+            .init_resource::<DeathFadeConfig>()
+            .init_resource::<LocalHighlightConfig>()
+            .insert_resource(SnakeObservers {
+                observers: self.observers.clone(),
+            })
+            .add_startup_system(load_settings)
+            .add_exit_system(GameState::Paused, clear_input_queues)
+            // Leaving the settings screen confirms any rebinds made on it
+            .add_exit_system(GameState::Settings, save_settings)
+            // As well as any zooming done during the game
+            .add_exit_system(GameState::Running, save_settings)
+            .add_event::<SnakeDeathEvent>()
+            .add_event::<GameAudioEvent>()
+            .add_event::<GameResultEvent>()
+            .add_event::<RemoveTailEvent>()
+            .add_event::<InputRejectedEvent>()
+            .add_event::<PredictedCollisionEvent>()
+            .add_event::<PredictionCorrectedEvent>()
+            // Each frame runs in this order, so a turn pressed this frame is taken by a move this frame:
+            // 1. PreUpdate: keys and gamepads are read, the console takes what it wants, then the player's turns are
+            //    queued (SnakeState::Input)
+            // 2. The fixed timestep stage, between PreUpdate and Update: snakes move zero or more times, each taking
+            //    one queued turn (SnakeState::Movement). Every step checks for collisions and food straight after
+            //    moving, and takes dead snakes out of play before the next step, so a frame that runs several steps
+            //    can't carry a head through anything.
+            // 3. Update: scoring, respawns, predictions and bots all see this frame's moves and deaths
+            // 4. PostUpdate: positions are interpolated and written to transforms for drawing
+            .add_fixed_timestep_system(
+                MOVEMENT_TIMESTEP,
+                0,
+                snake_movement.run_in_state(GameState::Running).label(SnakeState::Movement),
+            )
+            .add_fixed_timestep_system(
+                MOVEMENT_TIMESTEP,
+                0,
+                snake_collision
+                    .run_in_state(GameState::Running)
+                    .label(SnakeState::Collision)
+                    .after(SnakeState::Movement),
+            )
+            .add_fixed_timestep_system(
+                MOVEMENT_TIMESTEP,
+                0,
+                despawn_dead_snakes.run_in_state(GameState::Running).after(SnakeState::Collision),
+            )
+            .add_system(apply_tick_rate)
+            // Only ever steers the local snake. A bot driving it takes over from the player's input, and a spectator
+            // only watches. The countdown lets the player pick the direction to set off in.
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                snake_movement_input
+                    .run_in_state(GameState::Running)
+                    .run_unless_resource_exists::<BotController>()
+                    .run_unless_resource_exists::<Spectator>()
+                    .label(SnakeState::Input)
+                    .after(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gamepad_movement_input
+                    .run_in_state(GameState::Running)
+                    .run_unless_resource_exists::<BotController>()
+                    .run_unless_resource_exists::<Spectator>()
+                    .label(SnakeState::Input)
+                    .after(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                snake_movement_input
+                    .run_in_state(GameState::Countdown)
+                    .run_unless_resource_exists::<BotController>()
+                    .run_unless_resource_exists::<Spectator>()
+                    .label(SnakeState::Input)
+                    .after(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gamepad_movement_input
+                    .run_in_state(GameState::Countdown)
+                    .run_unless_resource_exists::<BotController>()
+                    .run_unless_resource_exists::<Spectator>()
+                    .label(SnakeState::Input)
+                    .after(InputSystem),
+            )
+            .add_system(respawn_snakes.run_in_state(GameState::Running))
+            // Keeps fading on the game over screen, so the snake that ended the game fades too
+            .add_system(death_fade.run_not_in_state(GameState::Paused))
+            .add_system(fade_speed_trails.run_not_in_state(GameState::Paused))
+            .add_system(expire_speed_boosts.run_in_state(GameState::Running))
+            .add_system(predict_collisions.run_in_state(GameState::Running).label(SnakeState::Prediction))
+            .add_system(tail_gradient.run_in_state(GameState::Running))
+            .add_system(highlight_local_snake.run_in_state(GameState::Running))
+            .add_system(apply_snake_textures.run_if_resource_exists::<SnakeTextures>())
+            .add_system(handle_remove_tail.run_in_state(GameState::Running))
+            .add_system(log_rejected_inputs.run_in_state(GameState::Running))
+            .add_system(log_predicted_collisions.run_in_state(GameState::Running).after(SnakeState::Prediction))
+            .add_system(notify_observers.run_in_state(GameState::Running));
+
+        #[cfg(feature = "validate_continuity")]
+        app.add_system(continuity::validate_continuity.run_in_state(GameState::Running));
+
+        // Draws from the final transforms, and only with a renderer to draw with
+        #[cfg(feature = "body_path")]
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            bodypath::render_snake_body_as_path
+                .run_in_state(GameState::Running)
+                .run_if_resource_exists::<Assets<Mesh>>()
+                .after(crate::common::components::PositionState::Translation),
+        )
+        .add_system(bodypath::show_dead_tails)
+        .add_enter_system(GameState::GameOver, bodypath::despawn_body_paths);
+    }
+}
+
+// Replaces the default preferences with the saved ones, or saves the current ones if there is no file yet
+fn load_settings(
+    mut commands: Commands,
+    settings_file: Option<Res<SettingsFile>>,
+    keybinds: Res<MovementKeybinds>,
+    color_preference: Res<PlayerColorPreference>,
+    deadzone: Res<GamepadDeadzone>,
+    zoom: Res<CameraZoom>,
+    theme: Res<Theme>,
+) {
+    let path = match settings_file {
+        Some(settings_file) => settings_file.settings_path.clone(),
+        None => return,
+    };
+    if path.exists() {
+        match ClientSettings::load_from_path(&path) {
+            Ok(settings) => {
+                commands.insert_resource(settings.keybinds);
+                commands.insert_resource(settings.color);
+                commands.insert_resource(settings.gamepad_deadzone);
+                commands.insert_resource(settings.zoom);
+                commands.insert_resource(settings.theme);
+            }
+            Err(e) => warn!("Could not load settings from {}, using defaults: {}", path.display(), e),
+        }
+    } else {
+        let settings = ClientSettings {
+            keybinds: keybinds.clone(),
+            color: *color_preference,
+            gamepad_deadzone: *deadzone,
+            zoom: *zoom,
+            theme: *theme,
+        };
+        if let Err(e) = settings.save_to_path(&path) {
+            warn!("Could not save settings to {}: {}", path.display(), e);
+        }
     }
 }
 
-const SNAKE_HEAD_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
-const SNAKE_SEGMENT_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+// Brightness of the first tail segment relative to the head, and the darkest any segment gets
+const TAIL_BRIGHTNESS: f32 = 0.45;
+const MIN_TAIL_BRIGHTNESS: f32 = 0.15;
 
-pub fn spawn_snake(mut commands: Commands) {
-    let mut speed_limiter = Timer::from_seconds(0.2, true);
-    // Instant tick the timer so snake starts moving immediately when spawned
-    speed_limiter.tick(Duration::from_secs_f32(0.2));
-    commands
+/// Where the local snake starts a game
+pub const SPAWN_POSITION: Position = Position { x: 3, y: 3 };
+// Rows between snakes starting a game side by side
+const SPAWN_SPACING: i32 = 3;
+
+/// Where the snake with `id` starts a game, on a row of its own below or above [`SPAWN_POSITION`] and always inside
+/// any border walls. Arenas with too few rows for every snake wrap back round and start some of them on top of each
+/// other.
+pub fn spawn_position(id: u8, arena: &ArenaConfig) -> Position {
+    let inset = if arena.mode == ArenaMode::SolidWalls { 1 } else { 0 };
+    let columns = (arena.width - 2 * inset).max(1);
+    let rows = (arena.height - 2 * inset).max(1);
+    Position {
+        x: inset + (SPAWN_POSITION.x - inset).rem_euclid(columns),
+        y: inset + (SPAWN_POSITION.y - inset + SPAWN_SPACING * id as i32).rem_euclid(rows),
+    }
+}
+
+/// Who a snake is and where it starts, for [`spawn_snake`]
+pub struct SnakeSpawn {
+    pub id: u8,
+    pub name: String,
+    pub color: Color,
+    pub team: Option<u8>,
+    pub position: Position,
+    /// Segments laid out behind the head
+    pub initial_tail: u8,
+}
+
+/// Spawns a snake at `spawn.position` heading right, with `spawn.initial_tail` segments laid out behind its head
+pub fn spawn_snake(
+    commands: &mut Commands,
+    spawn: SnakeSpawn,
+    arena: &ArenaConfig,
+    tail_gradient: &TailGradientConfig,
+    colorblind_mode: ColorblindMode,
+    registry: &mut SnakeRegistry,
+) {
+    let SnakeSpawn {
+        id,
+        name,
+        color,
+        team,
+        position,
+        initial_tail,
+    } = spawn;
+    let color = colorblind_mode.remap(team.map_or(color, |team| team_tint(color, team)));
+    let direction = Direction::Right;
+    // Only the cells back to a solid border are free, so a longer starting tail is cut short rather than laid into
+    // the wall
+    let initial_tail = match arena.mode {
+        ArenaMode::SolidWalls => initial_tail.min((position.x - 1).clamp(0, u8::MAX as i32) as u8),
+        ArenaMode::Wrap => initial_tail,
+    };
+    let mut tail = Vec::with_capacity(initial_tail as usize);
+    let mut tail_position = position;
+    for i in 0..initial_tail as usize {
+        tail_position = tail_position.wrapped_step(direction.opposite(), arena);
+        tail.push(spawn_tail(commands, tail_position, tail_color(color, i, tail_gradient)));
+    }
+    let head = commands
         .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: SNAKE_HEAD_COLOR,
-                ..default()
-            },
+            sprite: Sprite { color, ..default() },
             ..default()
         })
         .insert(SnakeHead {
-            input_direction: Direction::Right,
-            direction: Direction::Right,
-            tail: vec![],
-            timer: speed_limiter,
+            id,
+            color,
+            input_direction: direction,
+            direction,
+            tail,
+            speed: 1.0,
+            progress: 0.,
         })
-        .insert(Position { x: 3, y: 3 })
-        .insert(Size::square(0.8));
+        .insert(SnakeName(name))
+        .insert(InputQueue::default())
+        .insert(EatStreak::default())
+        .insert(position)
+        .insert(RenderPosition::from(position))
+        .insert(InterpolationBuffer::new(position))
+        .insert(Size::square(0.8))
+        .id();
+    if let Some(team) = team {
+        commands.entity(head).insert(Team(team));
+    }
+    registry.snakes.insert(id, head);
 }
 
+// Colors snakes on each team are pulled towards, cycling for teams past the last one
+const TEAM_COLORS: [Color; 4] = [
+    Color::rgb(0.9, 0.2, 0.2),
+    Color::rgb(0.2, 0.4, 0.9),
+    Color::rgb(0.2, 0.8, 0.3),
+    Color::rgb(0.9, 0.8, 0.2),
+];
+// How far a snake's color is pulled towards its team's, from 0 (not at all) to 1 (entirely)
+const TEAM_TINT: f32 = 0.5;
+
+/// A snake's color tinted towards its team's color
+pub fn team_tint(color: Color, team: u8) -> Color {
+    let tint = TEAM_COLORS[team as usize % TEAM_COLORS.len()];
+    let mix = |a: f32, b: f32| a + (b - a) * TEAM_TINT;
+    Color::rgba(
+        mix(color.r(), tint.r()),
+        mix(color.g(), tint.g()),
+        mix(color.b(), tint.b()),
+        color.a(),
+    )
+}
+
+/// Whether two snakes are teammates, which never kill each other
 #[inline]
-pub fn spawn_tail(commands: &mut Commands, position: Position) -> Entity {
+pub fn friendly(a: Option<&Team>, b: Option<&Team>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a == b)
+}
+
+#[inline]
+pub fn spawn_tail(commands: &mut Commands, position: Position, color: Color) -> Entity {
     commands
         .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: SNAKE_SEGMENT_COLOR,
-                ..default()
+            sprite: Sprite { color, ..default() },
+            // The body is drawn as one ribbon instead
+            visibility: Visibility {
+                is_visible: !cfg!(feature = "body_path"),
             },
             ..default()
         })
         .insert(Tail)
         .insert(position)
+        .insert(RenderPosition::from(position))
+        .insert(InterpolationBuffer::new(position))
         .insert(Size::square(0.7))
         .id()
 }
 
-fn snake_movement_input(keys: Res<Input<KeyCode>>, mut head_positions: Query<&mut SnakeHead>) {
-    for mut head in head_positions.iter_mut() {
-        let dir: Direction = if keys.pressed(KeyCode::Left) {
-            Direction::Left
-        } else if keys.pressed(KeyCode::Down) {
-            Direction::Down
-        } else if keys.pressed(KeyCode::Up) {
-            Direction::Up
-        } else if keys.pressed(KeyCode::Right) {
-            Direction::Right
-        } else {
-            head.input_direction
+/// Shade of the tail segment at `index` behind a head of the given color
+pub fn tail_color(head_color: Color, index: usize, config: &TailGradientConfig) -> Color {
+    let brightness =
+        (TAIL_BRIGHTNESS * (1. - config.falloff.clamp(0., 1.)).powi(index as i32)).max(MIN_TAIL_BRIGHTNESS);
+    Color::rgba(
+        head_color.r() * brightness,
+        head_color.g() * brightness,
+        head_color.b() * brightness,
+        head_color.a(),
+    )
+}
+
+// How much darker a colorblind mode's stripes are than the rest of the tail
+const STRIPE_BRIGHTNESS: f32 = 0.5;
+
+// Keeps existing segments in step with the head's color, the gradient config and the colorblind mode's stripes
+fn tail_gradient(
+    config: Res<TailGradientConfig>,
+    colorblind_mode: Res<ColorblindMode>,
+    heads: Query<&SnakeHead>,
+    mut sprites: Query<&mut Sprite, With<Tail>>,
+) {
+    for head in heads.iter() {
+        let stripe_period = colorblind_mode.stripe_period(head.id);
+        for (i, tail) in head.tail.iter().enumerate() {
+            if let Ok(mut sprite) = sprites.get_mut(*tail) {
+                let mut color = tail_color(head.color, i, &config);
+                if stripe_period.is_some_and(|period| (i + 1) % period == 0) {
+                    let [r, g, b, a] = color.as_rgba_f32();
+                    color = Color::rgba(r * STRIPE_BRIGHTNESS, g * STRIPE_BRIGHTNESS, b * STRIPE_BRIGHTNESS, a);
+                }
+                if sprite.color != color {
+                    sprite.color = color;
+                }
+            }
+        }
+    }
+}
+
+// Input resources are optional so the plugin also runs headless under MinimalPlugins
+#[allow(clippy::too_many_arguments)]
+fn snake_movement_input(
+    keys: Option<Res<Input<KeyCode>>>,
+    keybinds: Res<MovementKeybinds>,
+    inversion: Res<ControlInversion>,
+    buffer: Res<InputBufferConfig>,
+    timesteps: Res<FixedTimesteps>,
+    mut audio_events: EventWriter<GameAudioEvent>,
+    mut rejected_events: EventWriter<InputRejectedEvent>,
+    local_snake: Res<LocalSnake>,
+    registry: Res<SnakeRegistry>,
+    mut head_positions: Query<(&SnakeHead, &mut InputQueue)>,
+) {
+    let keys = match keys {
+        Some(keys) => keys,
+        None => return,
+    };
+    let (head, mut queue) =
+        match registry.snakes.get(&local_snake.0).and_then(|head| head_positions.get_mut(*head).ok()) {
+            Some(head) => head,
+            None => return,
+        };
+    let (dir, just_pressed) = match keyboard_direction(&keys, &keybinds) {
+        Some(held) => held,
+        None => return,
+    };
+    let dir = inversion.apply(dir);
+    if steer(head, &mut queue, dir, &buffer, ticks_until_move(&timesteps, head)) {
+        audio_events.send(GameAudioEvent::Turned);
+    } else if just_pressed && queue.reverses(dir, head.direction) {
+        // Only on the initial press, so holding the key doesn't repeat the event every frame
+        rejected_events.send(InputRejectedEvent { attempted: dir });
+    }
+}
+
+/// Direction held on the keyboard, and whether it was only pressed this frame
+fn keyboard_direction(keys: &Input<KeyCode>, keybinds: &MovementKeybinds) -> Option<(Direction, bool)> {
+    let held = [
+        (keybinds.left, Direction::Left),
+        (keybinds.down, Direction::Down),
+        (keybinds.up, Direction::Up),
+        (keybinds.right, Direction::Right),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keys.pressed(*key));
+
+    // Holding two perpendicular keys steers between them
+    #[cfg(feature = "diagonal")]
+    {
+        let (horizontal, vertical): (Vec<_>, Vec<_>) =
+            held.clone().partition(|(_, dir)| matches!(dir, Direction::Left | Direction::Right));
+        if let ([(horizontal_key, horizontal)], [(vertical_key, vertical)]) = (&horizontal[..], &vertical[..]) {
+            if let Some(dir) = Direction::diagonal(*horizontal, *vertical) {
+                return Some((dir, keys.any_just_pressed([*horizontal_key, *vertical_key])));
+            }
+        }
+    }
+
+    held.map(|(key, dir)| (dir, keys.just_pressed(key))).next()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn gamepad_movement_input(
+    gamepads: Option<Res<Gamepads>>,
+    axes: Option<Res<Axis<GamepadAxis>>>,
+    buttons: Option<Res<Input<GamepadButton>>>,
+    deadzone: Res<GamepadDeadzone>,
+    inversion: Res<ControlInversion>,
+    buffer: Res<InputBufferConfig>,
+    timesteps: Res<FixedTimesteps>,
+    mut audio_events: EventWriter<GameAudioEvent>,
+    local_snake: Res<LocalSnake>,
+    registry: Res<SnakeRegistry>,
+    mut head_positions: Query<(&SnakeHead, &mut InputQueue)>,
+) {
+    let (gamepads, axes, buttons) = match (gamepads, axes, buttons) {
+        (Some(gamepads), Some(axes), Some(buttons)) => (gamepads, axes, buttons),
+        _ => return,
+    };
+    let (head, mut queue) =
+        match registry.snakes.get(&local_snake.0).and_then(|head| head_positions.get_mut(*head).ok()) {
+            Some(head) => head,
+            None => return,
         };
-        if dir != head.direction.opposite() {
-            head.input_direction = dir;
+    for gamepad in gamepads.iter() {
+        if let Some(dir) = gamepad_direction(*gamepad, &axes, &buttons, deadzone.0).map(|dir| inversion.apply(dir)) {
+            if steer(head, &mut queue, dir, &buffer, ticks_until_move(&timesteps, head)) {
+                audio_events.send(GameAudioEvent::Turned);
+            }
+        }
+    }
+}
+
+/// Direction held on a gamepad's d-pad, falling back to whichever left stick axis is pushed furthest past the deadzone
+fn gamepad_direction(
+    gamepad: Gamepad,
+    axes: &Axis<GamepadAxis>,
+    buttons: &Input<GamepadButton>,
+    deadzone: f32,
+) -> Option<Direction> {
+    let pressed = |button_type| buttons.pressed(GamepadButton::new(gamepad, button_type));
+    if pressed(GamepadButtonType::DPadLeft) {
+        return Some(Direction::Left);
+    } else if pressed(GamepadButtonType::DPadDown) {
+        return Some(Direction::Down);
+    } else if pressed(GamepadButtonType::DPadUp) {
+        return Some(Direction::Up);
+    } else if pressed(GamepadButtonType::DPadRight) {
+        return Some(Direction::Right);
+    }
+
+    let x = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.);
+    let y = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.);
+    if x.abs().max(y.abs()) <= deadzone {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x < 0. { Direction::Left } else { Direction::Right })
+    } else {
+        Some(if y < 0. { Direction::Down } else { Direction::Up })
+    }
+}
+
+// Picks up changes to the tick rate at runtime
+fn apply_tick_rate(tick_rate: Res<ClientTickRate>, mut timesteps: ResMut<FixedTimesteps>) {
+    if tick_rate.is_changed() {
+        if let Some(timestep) = timesteps.get_mut(MOVEMENT_TIMESTEP) {
+            timestep.step = tick_rate.step() / MAX_SPEED;
         }
     }
 }
 
+// Drops turns queued before a pause so they don't fire the moment the game resumes
+fn clear_input_queues(mut queues: Query<&mut InputQueue>) {
+    for mut queue in queues.iter_mut() {
+        queue.directions.clear();
+    }
+}
+
+/// Queues a turn for the snake's next move, unless that would reverse it into itself or the input buffer has no
+/// room for it. Returns whether it was queued.
+#[inline]
+pub fn steer(
+    head: &SnakeHead,
+    queue: &mut InputQueue,
+    dir: Direction,
+    buffer: &InputBufferConfig,
+    ticks_until_move: f32,
+) -> bool {
+    buffer.in_window(queue.directions.len(), ticks_until_move) && queue.push(dir, head.direction, buffer.capacity)
+}
+
+/// Ticks left until `head` next moves, or a whole tick if movement is not running
+pub fn ticks_until_move(timesteps: &FixedTimesteps, head: &SnakeHead) -> f32 {
+    // Steps it takes to build up to the move, the first of which is already partway through
+    let steps = ((1. - head.progress) / progress_per_step(head)).ceil().max(1.);
+    timesteps.get(MOVEMENT_TIMESTEP).map_or(1., |timestep| {
+        let next_step = timestep.remaining().as_secs_f32() / timestep.timestep().as_secs_f32();
+        (next_step + steps - 1.) / MAX_SPEED as f32
+    })
+}
+
+// How far each movement step takes a snake towards its next move, so snakes at full speed move every step
+#[inline]
+fn progress_per_step(head: &SnakeHead) -> f32 {
+    head.speed.clamp(0., MAX_SPEED as f32) / MAX_SPEED as f32
+}
+
+// Runs MAX_SPEED times a tick, moving each snake whenever it has built up enough progress at its speed. snake_collision
+// and eat_food run after every step in the same stage, so a fast snake never moves two cells between checks.
+#[allow(clippy::too_many_arguments)]
 fn snake_movement(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    render: Res<RenderConfig>,
     time: Res<Time>,
-    mut head_positions: Query<(&mut Position, &mut SnakeHead)>,
+    mut throttle: ResMut<LogThrottle>,
+    mut tick: ResMut<MovementTick>,
+    mut step: ResMut<MovementStep>,
+    mut head_positions: Query<(Entity, &mut Position, &mut SnakeHead, &mut InputQueue, &Sprite)>,
     mut positions: Query<&mut Position, Without<SnakeHead>>,
+    mut buffers: Query<&mut InterpolationBuffer>,
 ) {
-    for (mut position, mut head) in head_positions.iter_mut() {
-        if head.timer.finished() {
-            // Tail
-            for (i, tail) in head.tail.iter().enumerate().rev() {
-                if i == 0 {
-                    let mut pos = positions.get_mut(*tail).unwrap();
-                    pos.x = position.x;
-                    pos.y = position.y;
-                } else {
-                    let next_x;
-                    let next_y;
-                    // Beat borrow checker
-                    {
-                        let next_pos = positions.get(head.tail[i - 1]).unwrap();
-                        next_x = next_pos.x;
-                        next_y = next_pos.y;
-                    }
-                    let mut pos = positions.get_mut(*tail).unwrap();
-                    pos.x = next_x;
-                    pos.y = next_y;
-                }
+    step.0 = (step.0 + 1) % MAX_SPEED;
+    if step.0 == 0 {
+        tick.0 += 1;
+    }
+    for (entity, mut position, mut head, mut queue, sprite) in head_positions.iter_mut() {
+        head.progress += progress_per_step(&head);
+        if head.progress < 1. {
+            continue;
+        }
+        head.progress -= 1.;
+        if head.speed > 1. {
+            spawn_speed_trail(&mut commands, *position, sprite.color, &arena, &render);
+        }
+
+        // Tail
+        // Drop segments that no longer have a Position (e.g. despawned out from under us) instead of panicking
+        let tail_len = head.tail.len();
+        head.tail.retain(|tail| positions.contains(*tail));
+        if head.tail.len() != tail_len && throttle.allow("lost_tail", time.time_since_startup()) {
+            warn!(
+                "Snake lost {} tail segment(s), dropping them from its tail",
+                tail_len - head.tail.len()
+            );
+        }
+        // Each segment moves into the spot of the one in front of it
+        let mut next = *position;
+        for tail in head.tail.iter() {
+            if let Ok(mut pos) = positions.get_mut(*tail) {
+                std::mem::swap(&mut *pos, &mut next);
             }
+        }
 
-            // Head
-            head.direction = head.input_direction;
-            match &head.input_direction {
-                Direction::Left => {
-                    position.x -= 1;
-                }
-                Direction::Up => {
-                    position.y += 1;
-                }
-                Direction::Right => {
-                    position.x += 1;
-                }
-                Direction::Down => {
-                    position.y -= 1;
-                }
+        // Head
+        // Take one queued turn per move, checked again in case the snake turned since it was queued
+        if let Some(dir) = queue.directions.pop_front() {
+            if dir != head.direction.opposite() {
+                head.input_direction = dir;
+            }
+        }
+        head.direction = head.input_direction;
+        *position = position.wrapped_step(head.direction, &arena);
+
+        // Glide between cells as quickly as the snake is moving through them
+        for segment in std::iter::once(entity).chain(head.tail.iter().copied()) {
+            if let Ok(mut buffer) = buffers.get_mut(segment) {
+                buffer.speed = head.speed;
+            }
+        }
+    }
+}
+
+fn spawn_speed_trail(
+    commands: &mut Commands,
+    position: Position,
+    color: Color,
+    arena: &ArenaConfig,
+    render: &RenderConfig,
+) {
+    let translation = cell_to_world(RenderPosition::from(position), arena, render);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: *color.clone().set_a(TRAIL_ALPHA),
+                ..default()
+            },
+            transform: Transform::from_translation(translation.extend(0.)),
+            ..default()
+        })
+        .insert(SpeedTrail {
+            timer: Timer::from_seconds(TRAIL_FADE_SECS, false),
+        })
+        .insert(Size::square(0.6));
+}
+
+// Fades out trails left behind by boosted snakes
+fn fade_speed_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trails: Query<(Entity, &mut SpeedTrail, &mut Sprite)>,
+) {
+    for (entity, mut trail, mut sprite) in trails.iter_mut() {
+        trail.timer.tick(time.delta());
+        if trail.timer.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_a(TRAIL_ALPHA * trail.timer.percent_left());
+        }
+    }
+}
+
+// Puts snakes back to normal speed once their boost runs out
+fn expire_speed_boosts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut boosted: Query<(Entity, &mut SpeedBoost, &mut SnakeHead)>,
+) {
+    for (entity, mut boost, mut head) in boosted.iter_mut() {
+        if boost.timer.tick(time.delta()).finished() {
+            head.speed = 1.0;
+            commands.entity(entity).remove::<SpeedBoost>();
+        }
+    }
+}
+
+/// Kills any snake whose head moved onto a wall or a snake body, including its own
+fn snake_collision(
+    heads: Query<(Entity, &Position, &SnakeHead, Option<&Team>), Changed<Position>>,
+    snakes: Query<(Entity, &SnakeHead, Option<&Team>)>,
+    bodies: Query<(Entity, &Position, Option<&Obstacle>), Or<(With<SnakeHead>, With<Tail>, With<Wall>)>>,
+    mut death_events: EventWriter<SnakeDeathEvent>,
+) {
+    // Snake each head and tail segment belongs to, to let teammates pass through each other
+    let mut owners = HashMap::new();
+    for (entity, snake, team) in snakes.iter() {
+        owners.insert(entity, (snake.id, team));
+        for tail in snake.tail.iter() {
+            owners.insert(*tail, (snake.id, team));
+        }
+    }
+    for (entity, position, head, team) in heads.iter() {
+        let cause = |body: Entity, obstacle: Option<&Obstacle>| match owners.get(&body) {
+            Some((owner, _)) if *owner == head.id => Some(CollisionCause::SelfBody),
+            Some((owner, owner_team)) => (!friendly(team, *owner_team)).then_some(CollisionCause::OtherSnake(*owner)),
+            None if obstacle.is_some() => Some(CollisionCause::Obstacle),
+            None => Some(CollisionCause::Wall),
+        };
+        let cause = bodies
+            .iter()
+            .filter(|(body, body_position, _)| *body != entity && *body_position == position)
+            .find_map(|(body, _, obstacle)| cause(body, obstacle));
+        if let Some(cause) = cause {
+            death_events.send(SnakeDeathEvent {
+                id: head.id,
+                head: entity,
+                position: *position,
+                cause,
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn despawn_dead_snakes(
+    mut commands: Commands,
+    mut death_events: EventReader<SnakeDeathEvent>,
+    mut audio_events: EventWriter<GameAudioEvent>,
+    mut registry: ResMut<SnakeRegistry>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut respawn_queue: ResMut<RespawnQueue>,
+    fade_config: Res<DeathFadeConfig>,
+    respawn_config: Res<RespawnConfig>,
+    heads: Query<(&SnakeHead, &SnakeName, Option<&Team>)>,
+) {
+    for event in death_events.iter() {
+        info!("Snake {} died at {:?} from {:?}", event.id, event.position, event.cause);
+        // A second event for the same snake, e.g. running into a body and a wall at once, finds it already gone
+        let entity = match registry.snakes.remove(&event.id) {
+            Some(entity) => entity,
+            None => continue,
+        };
+        audio_events.send(GameAudioEvent::Died);
+        *scoreboard.deaths.entry(event.id).or_default() += 1;
+        scoreboard.death_causes.insert(event.id, event.cause);
+        if let CollisionCause::OtherSnake(killer) = event.cause {
+            *scoreboard.kills.entry(killer).or_default() += 1;
+        }
+        // Strip everything that makes the body part of the game, leaving just the sprite to fade out
+        let fade = || DeathFade {
+            timer: Timer::from_seconds(fade_config.duration_secs.max(0.), false),
+        };
+        if let Ok((head, name, team)) = heads.get(entity) {
+            for tail in head.tail.iter() {
+                commands.entity(*tail).remove::<Tail>().remove::<Position>().insert(fade());
+            }
+            if respawn_config.enabled {
+                respawn_queue.pending.push(PendingRespawn {
+                    id: head.id,
+                    name: name.0.clone(),
+                    color: head.color,
+                    team: team.map(|team| team.0),
+                    timer: Timer::from_seconds(respawn_config.delay_secs.max(0.), false),
+                });
+            }
+        }
+        commands.entity(entity).remove::<SnakeHead>().remove::<Position>().insert(fade());
+    }
+}
+
+// Brings snakes back once their respawn delay is up, with no tail, on a free cell with room to move ahead
+#[allow(clippy::too_many_arguments)]
+fn respawn_snakes(
+    mut commands: Commands,
+    time: Res<Time>,
+    arena: Res<ArenaConfig>,
+    tail_gradient: Res<TailGradientConfig>,
+    mut rng: ResMut<GameRng>,
+    mut registry: ResMut<SnakeRegistry>,
+    mut respawn_queue: ResMut<RespawnQueue>,
+    heads: Query<(&Position, &SnakeHead)>,
+    tails: Query<&Position, With<Tail>>,
+    walls: Query<&Position, With<Wall>>,
+) {
+    let lethal = lethal_cells(heads.iter(), &tails, &walls);
+    let mut ready = Vec::new();
+    respawn_queue.pending.retain_mut(|pending| {
+        pending.timer.tick(time.delta());
+        if !pending.timer.finished() {
+            return true;
+        }
+        // Try again next frame if the arena is too crowded to find a spot straight away
+        let position = random_position(&arena, &mut rng.rng);
+        let free = |position: Position| !lethal.contains_key(&position);
+        if !free(position) || !free(position.wrapped_step(Direction::Right, &arena)) {
+            return true;
+        }
+        ready.push((pending.id, pending.name.clone(), pending.color, pending.team, position));
+        false
+    });
+    for (id, name, color, team, position) in ready {
+        info!("Snake {} respawned at {:?}", id, position);
+        // The color was already tinted and remapped when the snake first spawned
+        spawn_snake(
+            &mut commands,
+            SnakeSpawn {
+                id,
+                name,
+                color,
+                team: None,
+                position,
+                initial_tail: 0,
+            },
+            &arena,
+            &tail_gradient,
+            ColorblindMode::Off,
+            &mut registry,
+        );
+        if let Some(team) = team {
+            commands.entity(registry.snakes[&id]).insert(Team(team));
+        }
+    }
+}
+
+fn handle_remove_tail(
+    mut commands: Commands,
+    mut remove_events: EventReader<RemoveTailEvent>,
+    registry: Res<SnakeRegistry>,
+    mut heads: Query<&mut SnakeHead>,
+) {
+    for event in remove_events.iter() {
+        let mut head = match registry.snakes.get(&event.id).and_then(|head| heads.get_mut(*head).ok()) {
+            Some(head) => head,
+            None => continue,
+        };
+        let keep = head.tail.len().saturating_sub(event.count);
+        for tail in head.tail.drain(keep..) {
+            commands.entity(tail).despawn();
+        }
+    }
+}
+
+// How far towards white the local head gets at the peak of its pulse
+const HIGHLIGHT_BRIGHTNESS: f32 = 0.5;
+
+// Pulses the local head's sprite between its color and a brighter shade, leaving SnakeHead::color alone. Spectators
+// have no snake of their own, so theirs stays still.
+fn highlight_local_snake(
+    time: Res<Time>,
+    config: Res<LocalHighlightConfig>,
+    spectator: Option<Res<Spectator>>,
+    local_snake: Res<LocalSnake>,
+    registry: Res<SnakeRegistry>,
+    mut heads: Query<(&SnakeHead, &mut Sprite)>,
+) {
+    let (head, mut sprite) = match registry.snakes.get(&local_snake.0).and_then(|head| heads.get_mut(*head).ok()) {
+        Some(head) => head,
+        None => return,
+    };
+    let color = if config.enabled && spectator.is_none() {
+        let pulse = (1. - (time.seconds_since_startup() as f32 * config.speed * std::f32::consts::TAU).cos()) / 2.;
+        let [r, g, b, a] = head.color.as_rgba_f32();
+        let brighten = |c: f32| c + (1. - c) * HIGHLIGHT_BRIGHTNESS * pulse;
+        Color::rgba(brighten(r), brighten(g), brighten(b), a)
+    } else {
+        head.color
+    };
+    if sprite.color != color {
+        sprite.color = color;
+    }
+}
+
+fn death_fade(mut commands: Commands, time: Res<Time>, mut fading: Query<(Entity, &mut DeathFade, &mut Sprite)>) {
+    for (entity, mut fade, mut sprite) in fading.iter_mut() {
+        fade.timer.tick(time.delta());
+        if fade.timer.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_a(fade.timer.percent_left());
+        }
+    }
+}
+
+/// Cells that will still be lethal after every snake moves, mapped to the snake occupying them, or None for a wall
+pub fn lethal_cells<'a>(
+    heads: impl Iterator<Item = (&'a Position, &'a SnakeHead)>,
+    tails: &Query<&Position, With<Tail>>,
+    walls: &Query<&Position, With<Wall>>,
+) -> HashMap<Position, Option<u8>> {
+    let mut lethal: HashMap<Position, Option<u8>> = walls.iter().map(|pos| (*pos, None)).collect();
+    for (position, head) in heads {
+        lethal.insert(*position, Some(head.id));
+        // The last segment moves out of the way on the next move
+        for tail in head.tail.iter().rev().skip(1) {
+            if let Ok(tail_position) = tails.get(*tail) {
+                lethal.insert(*tail_position, Some(head.id));
             }
         }
+    }
+    lethal
+}
+
+/// Looks one move ahead for each snake and warns when it is about to run into a wall or body
+#[allow(clippy::too_many_arguments)]
+fn predict_collisions(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    mut death_events: EventReader<SnakeDeathEvent>,
+    heads: Query<(
+        Entity,
+        &Position,
+        &SnakeHead,
+        &InputQueue,
+        Option<&PredictedCollision>,
+        Option<&Team>,
+    )>,
+    tails: Query<&Position, With<Tail>>,
+    walls: Query<&Position, With<Wall>>,
+    mut predicted_events: EventWriter<PredictedCollisionEvent>,
+    mut corrected_events: EventWriter<PredictionCorrectedEvent>,
+) {
+    let lethal = lethal_cells(
+        heads.iter().map(|(_, position, head, ..)| (position, head)),
+        &tails,
+        &walls,
+    );
+    let teams: HashMap<u8, Option<&Team>> = heads.iter().map(|(_, _, head, .., team)| (head.id, team)).collect();
+    let dead: HashSet<u8> = death_events.iter().map(|event| event.id).collect();
+    for (entity, position, head, queue, predicted, team) in
+        heads.iter().filter(|(_, _, head, ..)| !dead.contains(&head.id))
+    {
+        let dir = queue.directions.front().copied().unwrap_or(head.input_direction);
+        let next = position.wrapped_step(dir, &arena);
+        // Running into a teammate is harmless
+        let threat = lethal.get(&next).filter(|other| match other {
+            Some(other) => *other == head.id || !friendly(team, teams.get(other).copied().flatten()),
+            None => true,
+        });
+
+        match (threat, predicted) {
+            (Some(other), predicted) if predicted.is_none_or(|predicted| predicted.other != *other) => {
+                commands.entity(entity).insert(PredictedCollision { other: *other });
+                predicted_events.send(PredictedCollisionEvent {
+                    id: head.id,
+                    other: *other,
+                });
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<PredictedCollision>();
+                corrected_events.send(PredictionCorrectedEvent { id: head.id });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn log_predicted_collisions(
+    mut predicted_events: EventReader<PredictedCollisionEvent>,
+    mut corrected_events: EventReader<PredictionCorrectedEvent>,
+) {
+    for event in predicted_events.iter() {
+        match event.other {
+            Some(other) => debug!("Snake {} is about to run into snake {}", event.id, other),
+            None => debug!("Snake {} is about to run into a wall", event.id),
+        }
+    }
+    for event in corrected_events.iter() {
+        debug!("Snake {} is no longer about to collide", event.id);
+    }
+}
+
+fn log_rejected_inputs(mut rejected_events: EventReader<InputRejectedEvent>) {
+    for event in rejected_events.iter() {
+        debug!(
+            "Ignored turning {:?}, the snake can't reverse into itself",
+            event.attempted
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate::common::constants::{MAX_OPPONENTS, MIN_ARENA_SIZE};
+
+    #[test]
+    fn remove_tail_despawns_the_last_segments() {
+        let mut world = World::new();
+        world.init_resource::<Events<RemoveTailEvent>>();
+
+        // Grow snake 0 to five segments
+        let mut state: SystemState<Commands> = SystemState::new(&mut world);
+        let mut commands = state.get_mut(&mut world);
+        let tail: Vec<Entity> = (0..5).map(|y| spawn_tail(&mut commands, Position { x: 0, y }, Color::WHITE)).collect();
+        let head = commands
+            .spawn()
+            .insert(SnakeHead {
+                id: 0,
+                color: Color::WHITE,
+                input_direction: Direction::Up,
+                direction: Direction::Up,
+                tail: tail.clone(),
+                speed: 1.0,
+                progress: 0.0,
+            })
+            .id();
+        state.apply(&mut world);
+        let mut registry = SnakeRegistry::default();
+        registry.snakes.insert(0, head);
+        world.insert_resource(registry);
+
+        world.resource_mut::<Events<RemoveTailEvent>>().send(RemoveTailEvent { id: 0, count: 2 });
+        SystemStage::single(handle_remove_tail).run(&mut world);
+
+        assert_eq!(world.get::<SnakeHead>(head).unwrap().tail, tail[..3]);
+        assert!(tail[..3].iter().all(|segment| world.get_entity(*segment).is_some()));
+        assert!(tail[3..].iter().all(|segment| world.get_entity(*segment).is_none()));
+    }
+
+    #[test]
+    fn spawn_positions_stay_inside_the_walls() {
+        // The smallest arenas, where the default spawn point is on or past the walls
+        for size in MIN_ARENA_SIZE..=MIN_ARENA_SIZE + 2 {
+            let arena = ArenaConfig {
+                width: size,
+                height: size,
+                mode: ArenaMode::SolidWalls,
+            };
+            for id in 0..=MAX_OPPONENTS {
+                let position = spawn_position(id, &arena);
+                assert!(
+                    (1..size - 1).contains(&position.x) && (1..size - 1).contains(&position.y),
+                    "snake {} spawned on or outside the walls at {:?} in a {}x{} arena",
+                    id,
+                    position,
+                    size,
+                    size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn initial_tail_stops_at_the_wall() {
+        let mut world = World::new();
+        let arena = ArenaConfig {
+            mode: ArenaMode::SolidWalls,
+            ..default()
+        };
+        let mut registry = SnakeRegistry::default();
+        let mut state: SystemState<Commands> = SystemState::new(&mut world);
+        let mut commands = state.get_mut(&mut world);
+        spawn_snake(
+            &mut commands,
+            SnakeSpawn {
+                id: 0,
+                name: String::new(),
+                color: Color::WHITE,
+                team: None,
+                position: Position { x: 3, y: 3 },
+                initial_tail: 10,
+            },
+            &arena,
+            &TailGradientConfig::default(),
+            ColorblindMode::Off,
+            &mut registry,
+        );
+        state.apply(&mut world);
 
-        head.timer.tick(time.delta());
+        let head = world.get::<SnakeHead>(registry.snakes[&0]).unwrap();
+        let tail: Vec<Position> = head.tail.iter().map(|segment| *world.get::<Position>(*segment).unwrap()).collect();
+        assert_eq!(tail, [Position { x: 2, y: 3 }, Position { x: 1, y: 3 }]);
     }
 }
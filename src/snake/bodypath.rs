@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::sprite::{ColorMaterial, Mesh2dHandle};
+use bevy::utils::HashMap;
+
+use crate::common::resources::RenderConfig;
+use crate::snake::components::{DeathFade, SnakeHead, Tail};
+
+// Width of the body as a fraction of a cell, matching the tail segment sprites it replaces
+const BODY_WIDTH: f32 = 0.7;
+// Between the tail segments and the head
+const BODY_Z: f32 = 0.5;
+
+// Ribbon drawn through a snake's segments in place of the individual tail sprites
+#[derive(Component)]
+pub struct BodyPath {
+    head: Entity,
+}
+
+// Rebuilds each snake's body ribbon from where its head and tail segments are drawn this frame
+pub fn render_snake_body_as_path(
+    mut commands: Commands,
+    render: Res<RenderConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    heads: Query<(Entity, &SnakeHead, &Transform)>,
+    tails: Query<&Transform, With<Tail>>,
+    paths: Query<(Entity, &BodyPath, &Mesh2dHandle, &Handle<ColorMaterial>)>,
+) {
+    let mut existing: HashMap<Entity, (&Mesh2dHandle, &Handle<ColorMaterial>)> = HashMap::new();
+    for (entity, path, mesh, material) in paths.iter() {
+        if heads.contains(path.head) {
+            existing.insert(path.head, (mesh, material));
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for (entity, head, transform) in heads.iter() {
+        let points: Vec<Vec2> = std::iter::once(transform.translation.truncate())
+            .chain(head.tail.iter().filter_map(|tail| tails.get(*tail).ok()).map(|tail| tail.translation.truncate()))
+            .collect();
+        let mesh = body_mesh(&points, render.cell_size);
+        match existing.get(&entity) {
+            Some((mesh_handle, material_handle)) => {
+                if let Some(existing_mesh) = meshes.get_mut(&mesh_handle.0) {
+                    *existing_mesh = mesh;
+                }
+                if let Some(material) = materials.get_mut(material_handle) {
+                    if material.color != head.color {
+                        material.color = head.color;
+                    }
+                }
+            }
+            None => {
+                commands
+                    .spawn_bundle(ColorMesh2dBundle {
+                        mesh: Mesh2dHandle(meshes.add(mesh)),
+                        material: materials.add(ColorMaterial::from(head.color)),
+                        transform: Transform::from_xyz(0., 0., BODY_Z),
+                        ..default()
+                    })
+                    .insert(BodyPath { head: entity });
+            }
+        }
+    }
+}
+
+/// Quads joining each pair of consecutive points, stretched half a width past both ends so corners are filled in.
+/// Pairs further than a cell and a half apart are where the snake wraps around the arena, so they are left unjoined.
+fn body_mesh(points: &[Vec2], cell_size: f32) -> Mesh {
+    let half_width = BODY_WIDTH * cell_size / 2.;
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    for pair in points.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        if from.distance(to) > cell_size * 1.5 {
+            continue;
+        }
+        // Segments grown this move still sit on top of the one before, so fall back to any direction
+        let along = (to - from).try_normalize().unwrap_or(Vec2::X) * half_width;
+        let across = along.perp();
+        let start = positions.len() as u32;
+        for corner in [
+            from - along - across,
+            from - along + across,
+            to + along + across,
+            to + along - across,
+        ] {
+            positions.push([corner.x, corner.y, 0.]);
+        }
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+
+    let vertices = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; vertices]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; vertices]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+// Tail sprites are hidden while the ribbon stands in for them, so show them again to fade out once their snake dies
+pub fn show_dead_tails(mut tails: Query<&mut Visibility, (Added<DeathFade>, Without<SnakeHead>)>) {
+    for mut visibility in tails.iter_mut() {
+        visibility.is_visible = true;
+    }
+}
+
+pub fn despawn_body_paths(mut commands: Commands, paths: Query<Entity, With<BodyPath>>) {
+    for path in paths.iter() {
+        commands.entity(path).despawn();
+    }
+}
@@ -1,19 +1,150 @@
-use bevy::prelude::{Component, Entity, SystemLabel, Timer};
+use std::collections::VecDeque;
+
+use bevy::prelude::{Color, Component, Entity, SystemLabel, Timer};
 
 use crate::common::components::Direction;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
 pub enum SnakeState {
+    Input,
     Movement,
+    Collision,
+    Prediction,
 }
 
 #[derive(Component)]
 pub struct SnakeHead {
+    pub id: u8,
+    pub color: Color,
     pub input_direction: Direction,
     pub direction: Direction,
     pub tail: Vec<Entity>,
-    pub timer: Timer,
+    /// Cells moved per tick, normally 1 and at most [`crate::snake::MAX_SPEED`]
+    pub speed: f32,
+    /// How far the snake has built up towards its next move, which it makes on reaching 1
+    pub progress: f32,
 }
 
 #[derive(Component)]
 pub struct Tail;
+
+/// Dead snake segment fading out, despawned once the timer finishes. It no longer has a grid position, so live
+/// snakes pass straight through it.
+#[derive(Component)]
+pub struct DeathFade {
+    pub timer: Timer,
+}
+
+/// Food a snake has eaten in a row, each within [`crate::snake::resources::StreakConfig::window_secs`] of the last.
+/// Purely for show, scores are unaffected.
+#[derive(Component, Default)]
+pub struct EatStreak {
+    pub count: u32,
+    /// When the snake last ate, in seconds since startup
+    pub last_eat_time: f64,
+}
+
+/// Raised speed from eating boost food, back to normal once the timer finishes
+#[derive(Component)]
+pub struct SpeedBoost {
+    pub timer: Timer,
+}
+
+/// Fading copy of a boosted snake's head left behind in the cell it just moved out of
+#[derive(Component)]
+pub struct SpeedTrail {
+    pub timer: Timer,
+}
+
+/// Team a snake plays on. Snakes on the same team share a tint and pass through each other.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Team(pub u8);
+
+/// Name shown above a snake's head, which may be empty
+#[derive(Component)]
+pub struct SnakeName(pub String);
+
+/// Turns an [`InputQueue`] holds unless [`crate::snake::resources::InputBufferConfig`] says otherwise
+pub const INPUT_QUEUE_CAPACITY: usize = 2;
+
+/// Turns waiting to be applied one per movement tick, so quick successive key presses aren't dropped
+#[derive(Component, Default)]
+pub struct InputQueue {
+    pub directions: VecDeque<Direction>,
+}
+
+impl InputQueue {
+    /// Queues a turn, unless `capacity` turns are already queued or it repeats or reverses the last direction the
+    /// snake will be facing
+    pub fn push(&mut self, dir: Direction, current: Direction, capacity: usize) -> bool {
+        let last = self.directions.back().copied().unwrap_or(current);
+        if self.directions.len() >= capacity || dir == last || dir == last.opposite() {
+            return false;
+        }
+        self.directions.push_back(dir);
+        true
+    }
+
+    /// Whether turning `dir` would reverse the snake into itself, given the turns already queued
+    pub fn reverses(&self, dir: Direction, current: Direction) -> bool {
+        dir == self.directions.back().copied().unwrap_or(current).opposite()
+    }
+}
+
+/// Marks a snake whose next move is predicted to run into something
+#[derive(Component)]
+pub struct PredictedCollision {
+    pub other: Option<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Queue after pushing left, up, right and down in turn to a snake heading up, and how many were accepted
+    fn fill(capacity: usize) -> (InputQueue, usize) {
+        let mut queue = InputQueue::default();
+        let accepted = [Direction::Left, Direction::Up, Direction::Right, Direction::Down]
+            .into_iter()
+            .filter(|dir| queue.push(*dir, Direction::Up, capacity))
+            .count();
+        (queue, accepted)
+    }
+
+    #[test]
+    fn push_stops_at_capacity() {
+        for capacity in 1..=3 {
+            let (queue, accepted) = fill(capacity);
+            assert_eq!(accepted, capacity, "capacity {}", capacity);
+            assert_eq!(queue.directions.len(), capacity, "capacity {}", capacity);
+        }
+        let (queue, _) = fill(3);
+        assert_eq!(queue.directions, [Direction::Left, Direction::Up, Direction::Right]);
+    }
+
+    #[test]
+    fn push_drops_reversals_and_repeats() {
+        let mut queue = InputQueue::default();
+        assert!(!queue.push(Direction::Up, Direction::Up, INPUT_QUEUE_CAPACITY));
+        assert!(!queue.push(Direction::Down, Direction::Up, INPUT_QUEUE_CAPACITY));
+        assert!(queue.push(Direction::Left, Direction::Up, INPUT_QUEUE_CAPACITY));
+        // Later turns are checked against the last queued one rather than where the snake is heading now
+        assert!(!queue.push(Direction::Left, Direction::Up, INPUT_QUEUE_CAPACITY));
+        assert!(!queue.push(Direction::Right, Direction::Up, INPUT_QUEUE_CAPACITY));
+        assert!(queue.reverses(Direction::Right, Direction::Up));
+        assert!(!queue.reverses(Direction::Down, Direction::Up));
+        assert_eq!(queue.directions, [Direction::Left]);
+    }
+
+    #[test]
+    fn push_drops_turns_once_full() {
+        let mut queue = InputQueue::default();
+        assert!(queue.push(Direction::Left, Direction::Up, INPUT_QUEUE_CAPACITY));
+        assert!(queue.push(Direction::Down, Direction::Up, INPUT_QUEUE_CAPACITY));
+        assert!(!queue.push(Direction::Right, Direction::Up, INPUT_QUEUE_CAPACITY));
+        assert_eq!(queue.directions, [Direction::Left, Direction::Down]);
+        // Room frees up as queued turns are applied
+        queue.directions.pop_front();
+        assert!(queue.push(Direction::Right, Direction::Up, INPUT_QUEUE_CAPACITY));
+    }
+}
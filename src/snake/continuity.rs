@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+
+use crate::common::components::Position;
+use crate::common::resources::{ArenaConfig, LogThrottle};
+use crate::snake::components::SnakeHead;
+
+// Furthest apart consecutive segments can be, counting moves along the grid, which a diagonal move takes two of
+const MAX_GAP: i32 = if cfg!(feature = "diagonal") { 2 } else { 1 };
+
+// Warns about any snake whose body has a gap in it after moving. A segment grown this move still sits on top of the
+// one before it, so only segments further apart than MAX_GAP are reported. A broken snake stays broken, so this is
+// throttled to keep it from repeating every move.
+pub fn validate_continuity(
+    time: Res<Time>,
+    arena: Res<ArenaConfig>,
+    mut throttle: ResMut<LogThrottle>,
+    heads: Query<(&Position, &SnakeHead), Changed<Position>>,
+    positions: Query<&Position, Without<SnakeHead>>,
+) {
+    for (position, head) in heads.iter() {
+        let mut previous = *position;
+        for (i, tail) in head.tail.iter().enumerate() {
+            let current = match positions.get(*tail) {
+                Ok(current) => *current,
+                Err(_) => continue,
+            };
+            if previous.distance(current, &arena) > MAX_GAP && throttle.allow("continuity", time.time_since_startup()) {
+                warn!(
+                    "Snake {} is not contiguous between segments {} and {}: {:?} -> {:?}",
+                    head.id,
+                    i,
+                    i + 1,
+                    previous,
+                    current
+                );
+            }
+            previous = current;
+        }
+    }
+}
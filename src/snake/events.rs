@@ -0,0 +1,81 @@
+use bevy::prelude::Entity;
+
+use crate::common::components::{Direction, Position};
+
+/// Fired when a snake runs into a wall or a snake body and dies
+pub struct SnakeDeathEvent {
+    pub id: u8,
+    /// Head entity, which keeps its name and sprite while it fades out
+    pub head: Entity,
+    /// Where the snake's head was when it died
+    pub position: Position,
+    pub cause: CollisionCause,
+}
+
+/// What a snake ran into to die
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionCause {
+    /// Its own tail
+    SelfBody,
+    /// Another snake's head or tail, credited with the kill
+    OtherSnake(u8),
+    /// The arena's border with [`crate::common::resources::ArenaMode::SolidWalls`]
+    Wall,
+    /// A wall placed from [`crate::common::resources::ObstacleConfig`]
+    Obstacle,
+}
+
+/// Fired when a snake's next move looks like it will kill it. Purely a warning, snake_collision still decides deaths
+pub struct PredictedCollisionEvent {
+    pub id: u8,
+    /// Snake that would be run into, which may be the snake itself, or None for a wall
+    pub other: Option<u8>,
+}
+
+/// Fired when a previously predicted collision is no longer going to happen, e.g. the snake turned away in time
+pub struct PredictionCorrectedEvent {
+    pub id: u8,
+}
+
+/// Fired when the player presses a direction that would reverse their snake into itself, so it is ignored
+pub struct InputRejectedEvent {
+    pub attempted: Direction,
+}
+
+/// Takes the last `count` segments off a snake's tail, or the whole tail if it is shorter
+pub struct RemoveTailEvent {
+    pub id: u8,
+    pub count: usize,
+}
+
+/// Fired when a round ends, either because one snake outlived the rest or because every snake died
+pub struct GameResultEvent {
+    /// Last snake standing, or None when the remaining snakes all died on the same tick
+    pub winner: Option<u8>,
+}
+
+/// Hooks for playing sounds. Nothing in the game listens for these, add a system reading them to hear anything:
+///
+/// ```ignore
+/// fn play_sounds(mut events: EventReader<GameAudioEvent>, audio: Res<Audio>, sounds: Res<MySounds>) {
+///     for event in events.iter() {
+///         match event {
+///             GameAudioEvent::AteFood => audio.play(sounds.crunch.clone()),
+///             GameAudioEvent::Turned => audio.play(sounds.click.clone()),
+///             GameAudioEvent::Died => audio.play(sounds.splat.clone()),
+///             GameAudioEvent::Spawned => audio.play(sounds.fanfare.clone()),
+///         };
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameAudioEvent {
+    /// A snake ate food and grew
+    AteFood,
+    /// The player's snake accepted a turn
+    Turned,
+    /// A snake died
+    Died,
+    /// The player's snake spawned at the start of a game
+    Spawned,
+}
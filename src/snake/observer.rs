@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::common::components::Position;
+use crate::snake::components::SnakeHead;
+use crate::snake::events::SnakeDeathEvent;
+
+/// Callbacks for reacting to snakes without writing systems, registered with
+/// [`crate::snake::SnakePlugin::with_observer`]. Every method does nothing by default, so only implement the ones
+/// you need.
+pub trait SnakeObserver: Send + Sync + 'static {
+    /// A snake appeared, at the start of a game or when respawning
+    fn on_spawn(&self, _id: u8, _position: Position) {}
+    /// A snake's head moved into a new cell
+    fn on_move(&self, _id: u8, _position: Position) {}
+    /// A snake's tail got longer
+    fn on_tail_grow(&self, _id: u8) {}
+    /// A snake died
+    fn on_death(&self, _id: u8) {}
+}
+
+/// Observer that logs everything it is told about
+pub struct LoggingObserver;
+
+impl SnakeObserver for LoggingObserver {
+    fn on_spawn(&self, id: u8, position: Position) {
+        info!("Snake {} spawned at {:?}", id, position);
+    }
+
+    fn on_move(&self, id: u8, position: Position) {
+        info!("Snake {} moved to {:?}", id, position);
+    }
+
+    fn on_tail_grow(&self, id: u8) {
+        info!("Snake {} grew", id);
+    }
+
+    fn on_death(&self, id: u8) {
+        info!("Snake {} died", id);
+    }
+}
+
+/// Observers registered on the plugin
+#[derive(Default)]
+pub struct SnakeObservers {
+    pub observers: Vec<Arc<dyn SnakeObserver>>,
+}
+
+// Tells every observer what happened to the snakes this frame
+pub fn notify_observers(
+    observers: Res<SnakeObservers>,
+    mut death_events: EventReader<SnakeDeathEvent>,
+    // Tail length of each snake as of the last frame, to tell when one grows
+    mut tail_lengths: Local<HashMap<u8, usize>>,
+    spawned: Query<(&SnakeHead, &Position), Added<SnakeHead>>,
+    moved: Query<(&SnakeHead, &Position), Changed<Position>>,
+    heads: Query<&SnakeHead>,
+) {
+    if observers.observers.is_empty() {
+        return;
+    }
+
+    let mut spawned_ids = HashSet::new();
+    for (head, position) in spawned.iter() {
+        spawned_ids.insert(head.id);
+        tail_lengths.insert(head.id, head.tail.len());
+        observers.observers.iter().for_each(|observer| observer.on_spawn(head.id, *position));
+    }
+    for (head, position) in moved.iter().filter(|(head, _)| !spawned_ids.contains(&head.id)) {
+        observers.observers.iter().for_each(|observer| observer.on_move(head.id, *position));
+    }
+    for head in heads.iter() {
+        let grew = tail_lengths.get(&head.id).is_some_and(|length| head.tail.len() > *length);
+        tail_lengths.insert(head.id, head.tail.len());
+        if grew {
+            observers.observers.iter().for_each(|observer| observer.on_tail_grow(head.id));
+        }
+    }
+    // A snake can die of more than one thing at once
+    let dead: HashSet<u8> = death_events.iter().map(|event| event.id).collect();
+    for id in dead {
+        tail_lengths.remove(&id);
+        observers.observers.iter().for_each(|observer| observer.on_death(id));
+    }
+}
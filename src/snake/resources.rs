@@ -0,0 +1,456 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use bevy::prelude::{Color, Entity, KeyCode, Timer};
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::common::components::Direction;
+use crate::common::constants::{MAX_TICK_RATE_HZ, MIN_TICK_RATE_HZ};
+use crate::snake::components::INPUT_QUEUE_CAPACITY;
+use crate::snake::events::CollisionCause;
+
+/// Keys used to steer the snake. Insert before [`crate::snake::SnakePlugin`] to override the arrow keys.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MovementKeybinds {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+}
+
+impl Default for MovementKeybinds {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+        }
+    }
+}
+
+/// Flips the player's controls along either axis, e.g. to steer from a mirrored perspective
+#[derive(Default)]
+pub struct ControlInversion {
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl ControlInversion {
+    /// Direction the snake should turn for the given input
+    pub fn apply(&self, dir: Direction) -> Direction {
+        let dir = if self.invert_x { dir.mirrored_x() } else { dir };
+        if self.invert_y {
+            dir.mirrored_y()
+        } else {
+            dir
+        }
+    }
+}
+
+/// How far a gamepad stick must be pushed, from 0 to 1, before it steers the snake
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct GamepadDeadzone(pub f32);
+
+impl Default for GamepadDeadzone {
+    fn default() -> Self {
+        Self(0.2)
+    }
+}
+
+/// How many times per second snakes move. Change it at runtime to speed the game up or slow it down.
+///
+/// Rendered positions glide between cells over [`crate::common::resources::InterpolationConfig::buffer_ms`], which
+/// should be kept at about `1000 / hz` so snakes neither stall on a cell nor jump the last part of a move.
+#[derive(Clone, Copy)]
+pub struct ClientTickRate {
+    pub hz: f32,
+}
+
+impl ClientTickRate {
+    /// Time between moves, with the rate clamped into the range validate_configs enforces on startup. Keeps a zero,
+    /// negative or NaN rate from producing an infinite step, and a huge one from producing a zero step.
+    pub fn step(&self) -> Duration {
+        // clamp passes NaN straight through
+        let hz = if self.hz.is_nan() { MIN_TICK_RATE_HZ } else { self.hz.clamp(MIN_TICK_RATE_HZ, MAX_TICK_RATE_HZ) };
+        Duration::from_secs_f32(1. / hz)
+    }
+}
+
+impl Default for ClientTickRate {
+    fn default() -> Self {
+        Self { hz: 5.0 }
+    }
+}
+
+/// How long a dead snake takes to fade out
+pub struct DeathFadeConfig {
+    pub duration_secs: f32,
+}
+
+impl Default for DeathFadeConfig {
+    fn default() -> Self {
+        Self { duration_secs: 0.5 }
+    }
+}
+
+/// Gentle pulse on the local snake's head so players can spot their own snake
+pub struct LocalHighlightConfig {
+    pub enabled: bool,
+    /// Pulses per second
+    pub speed: f32,
+}
+
+impl Default for LocalHighlightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            speed: 1.5,
+        }
+    }
+}
+
+/// Name shown above this player's snake. Left empty, the snake is labelled by its id instead.
+#[derive(Default)]
+pub struct PlayerName(pub String);
+
+/// Team this player's snake plays on, or None to play alone
+#[derive(Default)]
+pub struct PlayerTeam(pub Option<u8>);
+
+/// Movement ticks since the current game started
+#[derive(Default)]
+pub struct MovementTick(pub u64);
+
+/// Movement steps into the current [`MovementTick`], out of [`crate::snake::MAX_SPEED`]
+#[derive(Default)]
+pub struct MovementStep(pub u32);
+
+/// Id of the snake controlled by this player
+pub struct LocalSnake(pub u8);
+
+/// Head entity of every live snake, keyed by snake id. Kept up to date by [`crate::snake::spawn_snake`] and as
+/// snakes die.
+#[derive(Default)]
+pub struct SnakeRegistry {
+    pub snakes: HashMap<u8, Entity>,
+}
+
+/// Food eaten by each snake, keyed by snake id
+#[derive(Default)]
+pub struct Scoreboard {
+    pub scores: HashMap<u8, u32>,
+    /// Times each snake has died, which only goes past one with [`RespawnConfig`] enabled
+    pub deaths: HashMap<u8, u32>,
+    /// Team of each snake that plays on one
+    pub teams: HashMap<u8, u8>,
+    /// Snakes each snake has killed by getting in their way
+    pub kills: HashMap<u8, u32>,
+    /// What each snake ran into the last time it died
+    pub death_causes: HashMap<u8, CollisionCause>,
+}
+
+/// How quickly tail segments darken the further they are from the head
+pub struct TailGradientConfig {
+    /// Fraction of brightness lost per segment, from 0 (flat color) to 1
+    pub falloff: f32,
+}
+
+impl Default for TailGradientConfig {
+    fn default() -> Self {
+        Self { falloff: 0.05 }
+    }
+}
+
+/// Palette snakes are drawn with, for players who struggle to tell the default colors apart. Only changes how
+/// snakes look on this screen, and anything other than Off also stripes each tail with a per snake pattern.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorblindMode {
+    #[default]
+    Off,
+    Deuteranopia,
+    Protanopia,
+    HighContrast,
+}
+
+impl ColorblindMode {
+    /// `color` as it should be drawn in this mode
+    pub fn remap(self, color: Color) -> Color {
+        let [r, g, b, a] = color.as_rgba_f32();
+        // Red-green differences are what both kinds of red-green colorblindness lose, so move them onto blue
+        let red_green = r - g;
+        match self {
+            ColorblindMode::Off => color,
+            ColorblindMode::Deuteranopia => Color::rgba(r, g, (b + red_green * 0.7).clamp(0., 1.), a),
+            // Reds also look dark with protanopia, so lift them as well
+            ColorblindMode::Protanopia => Color::rgba(r, (g + r * 0.3).clamp(0., 1.), (b + red_green).clamp(0., 1.), a),
+            ColorblindMode::HighContrast => {
+                let channel = |c: f32| if c >= 0.5 { 1. } else { 0. };
+                match (channel(r), channel(g), channel(b)) {
+                    // Black would vanish into the background
+                    (r, g, b) if r + g + b == 0. => Color::rgba(1., 1., 1., a),
+                    (r, g, b) => Color::rgba(r, g, b, a),
+                }
+            }
+        }
+    }
+
+    /// Every how many tail segments snake `id` has a darker stripe, or None when tails are not patterned
+    pub fn stripe_period(self, id: u8) -> Option<usize> {
+        match self {
+            ColorblindMode::Off => None,
+            _ => Some(2 + id as usize % 3),
+        }
+    }
+}
+
+impl FromStr for ColorblindMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "deuteranopia" => Ok(Self::Deuteranopia),
+            "protanopia" => Ok(Self::Protanopia),
+            "high-contrast" => Ok(Self::HighContrast),
+            _ => Err(format!(
+                "Unknown colorblind mode '{}', expected one of: off, deuteranopia, protanopia, high-contrast",
+                s
+            )),
+        }
+    }
+}
+
+/// How far ahead turns can be queued, trading forgiveness for mistimed presses against accidental extra turns
+pub struct InputBufferConfig {
+    /// Most turns waiting in a snake's queue at once
+    pub capacity: usize,
+    /// Furthest ahead of the move it will be applied on, in ticks, that a turn is still accepted. Turns pressed
+    /// earlier than that are dropped. None accepts turns however early, as long as there is room in the queue.
+    pub window_ticks: Option<f32>,
+}
+
+impl Default for InputBufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: INPUT_QUEUE_CAPACITY,
+            window_ticks: None,
+        }
+    }
+}
+
+impl InputBufferConfig {
+    /// Whether a turn pressed `ticks_until_move` ticks before the next move falls in the window, with `queued`
+    /// turns already waiting to be applied before it
+    pub fn in_window(&self, queued: usize, ticks_until_move: f32) -> bool {
+        self.window_ticks.is_none_or(|window| queued as f32 + ticks_until_move <= window)
+    }
+}
+
+/// Whether dead snakes come back, for continuous play. While enabled, rounds never end on their own.
+pub struct RespawnConfig {
+    pub enabled: bool,
+    /// How long a snake stays dead before respawning
+    pub delay_secs: f32,
+}
+
+impl Default for RespawnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_secs: 3.0,
+        }
+    }
+}
+
+/// A dead snake waiting to respawn, remembering what it looked like
+pub struct PendingRespawn {
+    pub id: u8,
+    pub name: String,
+    /// Color as it was drawn, with any team tint and colorblind remapping already applied
+    pub color: Color,
+    pub team: Option<u8>,
+    pub timer: Timer,
+}
+
+/// Dead snakes waiting out [`RespawnConfig::delay_secs`]
+#[derive(Default)]
+pub struct RespawnQueue {
+    pub pending: Vec<PendingRespawn>,
+}
+
+/// How quickly snakes must keep eating to build an [`crate::snake::components::EatStreak`]
+pub struct StreakConfig {
+    /// Longest gap between two foods that still continues a streak
+    pub window_secs: f32,
+}
+
+impl Default for StreakConfig {
+    fn default() -> Self {
+        Self { window_secs: 3.0 }
+    }
+}
+
+/// Longest a tail can grow, or None for no limit. Food still scores once a snake is at the limit.
+#[derive(Default)]
+pub struct TailLengthConfig {
+    pub max_length: Option<usize>,
+}
+
+/// How snakes look when they first spawn
+#[derive(Default)]
+pub struct SnakeSpawnConfig {
+    /// Tail segments a snake starts with, laid out in a line behind its head. Cut short if the line would reach a
+    /// solid border.
+    pub initial_tail: u8,
+}
+
+/// Color the player would like their snake to be, with each channel from 0 to 1
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerColorPreference {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl PlayerColorPreference {
+    pub fn is_valid(&self) -> bool {
+        [self.r, self.g, self.b].iter().all(|c| (0.0..=1.0).contains(c))
+    }
+
+    /// The preferred color, with any out of range channel clamped into [0, 1]
+    pub fn color(&self) -> Color {
+        Color::rgb(self.r.clamp(0., 1.), self.g.clamp(0., 1.), self.b.clamp(0., 1.))
+    }
+}
+
+impl Default for PlayerColorPreference {
+    fn default() -> Self {
+        Self { r: 0.7, g: 0.7, b: 0.7 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_window_accepts_turns_close_enough_to_the_move() {
+        let config = InputBufferConfig {
+            capacity: 3,
+            window_ticks: Some(1.0),
+        };
+        assert!(config.in_window(0, 0.5));
+        assert!(config.in_window(0, 1.0));
+        assert!(!config.in_window(0, 1.5));
+        // Each queued turn pushes the new one a tick further away from being applied
+        assert!(!config.in_window(1, 0.5));
+    }
+
+    #[test]
+    fn no_window_accepts_any_turn() {
+        let config = InputBufferConfig::default();
+        assert!(config.in_window(0, 100.0));
+        assert!(config.in_window(5, 0.0));
+    }
+
+    #[test]
+    fn inversion_mirrors_the_flipped_axes() {
+        use Direction::*;
+        // (invert_x, invert_y, input, expected)
+        let cases = [
+            (false, false, Left, Left),
+            (false, false, Up, Up),
+            (true, false, Left, Right),
+            (true, false, Right, Left),
+            (true, false, Up, Up),
+            (true, false, Down, Down),
+            (false, true, Left, Left),
+            (false, true, Right, Right),
+            (false, true, Up, Down),
+            (false, true, Down, Up),
+            (true, true, Left, Right),
+            (true, true, Right, Left),
+            (true, true, Up, Down),
+            (true, true, Down, Up),
+        ];
+        for (invert_x, invert_y, input, expected) in cases {
+            let inversion = ControlInversion { invert_x, invert_y };
+            assert_eq!(
+                inversion.apply(input),
+                expected,
+                "x {} y {} {:?}",
+                invert_x,
+                invert_y,
+                input
+            );
+        }
+    }
+
+    #[cfg(feature = "diagonal")]
+    #[test]
+    fn inversion_mirrors_diagonals() {
+        use Direction::*;
+        let cases = [
+            (true, false, UpLeft, UpRight),
+            (false, true, UpLeft, DownLeft),
+            (true, true, UpLeft, DownRight),
+        ];
+        for (invert_x, invert_y, input, expected) in cases {
+            let inversion = ControlInversion { invert_x, invert_y };
+            assert_eq!(
+                inversion.apply(input),
+                expected,
+                "x {} y {} {:?}",
+                invert_x,
+                invert_y,
+                input
+            );
+        }
+    }
+
+    fn assert_color(actual: Color, expected: [f32; 4]) {
+        let actual = actual.as_rgba_f32();
+        assert!(
+            actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-5),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn colorblind_palettes_remap_colors() {
+        let red = Color::rgba(1., 0., 0., 0.5);
+        let green = Color::rgb(0., 1., 0.);
+        let dark = Color::rgb(0.2, 0.2, 0.2);
+        assert_color(ColorblindMode::Off.remap(red), [1., 0., 0., 0.5]);
+        // Red gains blue, green is left alone
+        assert_color(ColorblindMode::Deuteranopia.remap(red), [1., 0., 0.7, 0.5]);
+        assert_color(ColorblindMode::Deuteranopia.remap(green), [0., 1., 0., 1.]);
+        assert_color(ColorblindMode::Protanopia.remap(red), [1., 0.3, 1., 0.5]);
+        assert_color(ColorblindMode::Protanopia.remap(green), [0., 1., 0., 1.]);
+        // Every channel is pushed to 0 or 1, and black turns white
+        assert_color(
+            ColorblindMode::HighContrast.remap(Color::rgb(0.7, 0.3, 0.6)),
+            [1., 0., 1., 1.],
+        );
+        assert_color(ColorblindMode::HighContrast.remap(dark), [1., 1., 1., 1.]);
+    }
+
+    #[test]
+    fn only_colorblind_palettes_stripe_tails() {
+        assert_eq!(ColorblindMode::Off.stripe_period(0), None);
+        for mode in [
+            ColorblindMode::Deuteranopia,
+            ColorblindMode::Protanopia,
+            ColorblindMode::HighContrast,
+        ] {
+            let periods: Vec<_> = (0..4).map(|id| mode.stripe_period(id)).collect();
+            assert_eq!(periods, [Some(2), Some(3), Some(4), Some(2)], "{:?}", mode);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::common::resources::{CameraZoom, Theme};
+use crate::snake::resources::{GamepadDeadzone, MovementKeybinds, PlayerColorPreference};
+
+/// Player preferences saved between sessions. Fields missing from the file fall back to their defaults.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    pub keybinds: MovementKeybinds,
+    pub color: PlayerColorPreference,
+    pub gamepad_deadzone: GamepadDeadzone,
+    pub zoom: CameraZoom,
+    pub theme: Theme,
+}
+
+impl ClientSettings {
+    pub fn load_from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(
+            path,
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Saves the current preferences if any changed since this system last ran, and if they are being persisted
+pub fn save_settings(
+    settings_file: Option<Res<SettingsFile>>,
+    keybinds: Res<MovementKeybinds>,
+    color_preference: Res<PlayerColorPreference>,
+    deadzone: Res<GamepadDeadzone>,
+    zoom: Res<CameraZoom>,
+    theme: Res<Theme>,
+) {
+    // Also runs on every pause and countdown, which usually change nothing
+    let changed = keybinds.is_changed()
+        || color_preference.is_changed()
+        || deadzone.is_changed()
+        || zoom.is_changed()
+        || theme.is_changed();
+    if !changed {
+        return;
+    }
+    if let Some(settings_file) = settings_file {
+        let settings = ClientSettings {
+            keybinds: keybinds.clone(),
+            color: *color_preference,
+            gamepad_deadzone: *deadzone,
+            zoom: *zoom,
+            theme: *theme,
+        };
+        if let Err(e) = settings.save_to_path(&settings_file.settings_path) {
+            warn!(
+                "Could not save settings to {}: {}",
+                settings_file.settings_path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Where [`ClientSettings`] are loaded from on startup. Settings are only persisted when this resource exists.
+pub struct SettingsFile {
+    pub settings_path: PathBuf,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        Self {
+            settings_path: PathBuf::from("settings.ron"),
+        }
+    }
+}
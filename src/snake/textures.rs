@@ -0,0 +1,110 @@
+use std::f32::consts::FRAC_PI_2;
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::common::components::{Direction, Position};
+use crate::common::resources::ArenaConfig;
+use crate::snake::components::{SnakeHead, Tail};
+
+/// Images to draw snakes with instead of solid squares, tinted with each snake's color. All of them are drawn
+/// pointing right:
+///
+/// - `head` faces right
+/// - `body` runs from the left edge to the right edge
+/// - `corner` joins the right edge to the bottom edge
+/// - `tail` is the tip, joining the right edge to the segment in front of it
+pub struct SnakeTextures {
+    pub head: Handle<Image>,
+    pub body: Handle<Image>,
+    pub corner: Handle<Image>,
+    pub tail: Handle<Image>,
+}
+
+impl SnakeTextures {
+    /// Loads `head.png`, `body.png`, `corner.png` and `tail.png` from `dir` in the assets folder
+    pub fn load(asset_server: &AssetServer, dir: &Path) -> Self {
+        Self {
+            head: asset_server.load(dir.join("head.png")),
+            body: asset_server.load(dir.join("body.png")),
+            corner: asset_server.load(dir.join("corner.png")),
+            tail: asset_server.load(dir.join("tail.png")),
+        }
+    }
+}
+
+const STRAIGHT: [Direction; 4] = [Direction::Right, Direction::Up, Direction::Left, Direction::Down];
+
+// Quarter turns anticlockwise that take something pointing right to pointing in `dir`
+fn quarter_turns(dir: Direction) -> usize {
+    STRAIGHT.iter().position(|straight| *straight == dir).unwrap_or(0)
+}
+
+// Which way `to` is from the neighbouring cell `from`, or None if they are not side by side
+fn direction_to(from: Position, to: Position, arena: &ArenaConfig) -> Option<Direction> {
+    STRAIGHT.into_iter().find(|dir| from.wrapped_step(*dir, arena) == to)
+}
+
+// Picks each segment's image and turns it to join up with the segments on either side, as the snake turns
+pub fn apply_snake_textures(
+    textures: Res<SnakeTextures>,
+    arena: Res<ArenaConfig>,
+    mut heads: Query<(&SnakeHead, &Position, &mut Handle<Image>, &mut Sprite, &mut Transform)>,
+    mut tails: Query<(&Position, &mut Handle<Image>, &mut Sprite, &mut Transform), (With<Tail>, Without<SnakeHead>)>,
+) {
+    for (head, head_position, mut image, mut sprite, mut transform) in heads.iter_mut() {
+        apply(
+            &mut image,
+            &mut sprite,
+            &mut transform,
+            &textures.head,
+            quarter_turns(head.direction),
+        );
+
+        let positions: Vec<Position> = std::iter::once(*head_position)
+            .chain(head.tail.iter().filter_map(|tail| tails.get(*tail).ok().map(|(position, ..)| *position)))
+            .collect();
+        for (i, tail) in head.tail.iter().enumerate() {
+            let (position, mut image, mut sprite, mut transform) = match tails.get_mut(*tail) {
+                Ok(tail) => tail,
+                Err(_) => continue,
+            };
+            let ahead = positions.get(i).and_then(|ahead| direction_to(*position, *ahead, &arena));
+            let behind = positions.get(i + 2).and_then(|behind| direction_to(*position, *behind, &arena));
+            let (texture, turns) = match (ahead, behind) {
+                (Some(ahead), None) if i + 1 == head.tail.len() => (&textures.tail, quarter_turns(ahead)),
+                (Some(ahead), Some(behind)) if ahead != behind.opposite() => {
+                    // The corner's edges start out right and down, and each quarter turn moves both along one
+                    let turns = (0..4)
+                        .find(|turns| {
+                            let edges = [STRAIGHT[*turns], STRAIGHT[(turns + 3) % 4]];
+                            edges.contains(&ahead) && edges.contains(&behind)
+                        })
+                        .unwrap_or(0);
+                    (&textures.corner, turns)
+                }
+                // Straight, or on top of a neighbour because it only just grew
+                (ahead, _) => (&textures.body, ahead.map_or(0, quarter_turns)),
+            };
+            apply(&mut image, &mut sprite, &mut transform, texture, turns);
+        }
+    }
+}
+
+fn apply(
+    image: &mut Handle<Image>,
+    sprite: &mut Sprite,
+    transform: &mut Transform,
+    texture: &Handle<Image>,
+    turns: usize,
+) {
+    if *image != *texture {
+        *image = texture.clone();
+        // size_scaling scales sprites up from a single pixel, so draw the image at that size whatever its resolution
+        sprite.custom_size = Some(Vec2::ONE);
+    }
+    let rotation = Quat::from_rotation_z(turns as f32 * FRAC_PI_2);
+    if transform.rotation != rotation {
+        transform.rotation = rotation;
+    }
+}
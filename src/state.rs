@@ -3,5 +3,9 @@ pub enum GameState {
     MainMenu,
     Paused,
     PreGame,
+    /// Counting down to the start, with the snakes in place but not yet moving
+    Countdown,
     Running,
+    GameOver,
+    Settings,
 }
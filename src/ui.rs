@@ -1,19 +1,44 @@
 use bevy::prelude::*;
 use iyes_loopless::prelude::*;
 
+use crate::common::components::PositionState;
+use crate::common::resources::{ShowGrid, ShowMovePreview};
 use crate::state::GameState;
 use crate::ui::components::*;
+use crate::ui::countdown::*;
+use crate::ui::gameover::*;
+use crate::ui::grid::*;
+use crate::ui::killfeed::*;
 use crate::ui::mainmenu::*;
+#[cfg(feature = "minimap")]
+use crate::ui::minimap::*;
+use crate::ui::movepreview::*;
+use crate::ui::namelabels::*;
+use crate::ui::pause::*;
+use crate::ui::scoreboard::*;
+use crate::ui::settings::*;
+use crate::ui::streak::*;
 
 mod components;
+mod countdown;
+mod gameover;
+mod grid;
+mod killfeed;
 mod mainmenu;
+#[cfg(feature = "minimap")]
+mod minimap;
+mod movepreview;
+mod namelabels;
+mod pause;
+mod scoreboard;
+mod settings;
+mod streak;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_loopless_state(GameState::MainMenu)
-            .add_enter_system(GameState::MainMenu, main_menu_setup)
+        app.add_enter_system(GameState::MainMenu, main_menu_setup)
             // Common systems to all screens that handles buttons behaviour
             .add_system_set(
                 ConditionSet::new()
@@ -22,6 +47,68 @@ impl Plugin for UiPlugin {
                     .with_system(button_system)
                     .into(),
             )
-            .add_exit_system(GameState::MainMenu, despawn_screen::<OnMainMenuScreen>);
+            .add_exit_system(GameState::MainMenu, despawn_screen::<OnMainMenuScreen>)
+            .add_enter_system(GameState::Settings, settings_setup)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(GameState::Settings)
+                    .with_system(menu_action)
+                    .with_system(button_system)
+                    .with_system(rebind_ui)
+                    .into(),
+            )
+            .add_exit_system(GameState::Settings, despawn_screen::<OnSettingsScreen>)
+            .add_enter_system(GameState::PreGame, scoreboard_setup)
+            .add_system(update_scoreboard_ui.run_in_state(GameState::Running))
+            .add_enter_system(GameState::PreGame, killfeed_setup)
+            .add_system(record_kills.run_in_state(GameState::Running))
+            .add_system(killfeed_ui.run_in_state(GameState::Running))
+            .add_system(show_streaks.run_in_state(GameState::Running))
+            .add_system(animate_streak_popups.run_in_state(GameState::Running))
+            .add_enter_system(GameState::GameOver, despawn_screen::<StreakPopup>)
+            .add_enter_system(GameState::Countdown, countdown_setup)
+            .add_system(countdown_ui.run_in_state(GameState::Countdown))
+            .add_exit_system(GameState::Countdown, despawn_screen::<OnCountdownScreen>)
+            // Follows the heads' final transforms for this frame
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                render_name_labels.run_in_state(GameState::Running).after(PositionState::Translation),
+            )
+            .add_enter_system(GameState::GameOver, despawn_screen::<NameLabel>)
+            .add_system(toggle_pause)
+            .add_system(toggle_grid.run_in_state(GameState::Running))
+            // Nothing runs while the grid is hidden, besides clearing away lines left from when it was shown
+            .add_system(grid_overlay.run_in_state(GameState::Running).run_if_resource_exists::<ShowGrid>())
+            .add_system(
+                despawn_screen::<GridLine>.run_in_state(GameState::Running).run_unless_resource_exists::<ShowGrid>(),
+            )
+            .add_enter_system(GameState::GameOver, despawn_screen::<GridLine>)
+            .add_system(toggle_move_preview.run_in_state(GameState::Running))
+            .add_system(show_move_preview.run_in_state(GameState::Running).run_if_resource_exists::<ShowMovePreview>())
+            .add_system(
+                despawn_screen::<MovePreview>
+                    .run_in_state(GameState::Running)
+                    .run_unless_resource_exists::<ShowMovePreview>(),
+            )
+            .add_enter_system(GameState::GameOver, despawn_screen::<MovePreview>)
+            .add_enter_system(GameState::Paused, paused_overlay)
+            .add_exit_system(GameState::Paused, despawn_screen::<OnPausedScreen>)
+            .add_enter_system(GameState::GameOver, game_over_setup)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(GameState::GameOver)
+                    .with_system(menu_action)
+                    .with_system(button_system)
+                    .with_system(game_over_input)
+                    .into(),
+            )
+            .add_exit_system(GameState::GameOver, despawn_screen::<OnGameOverScreen>)
+            .add_exit_system(GameState::GameOver, despawn_screen::<ScoreboardUi>)
+            .add_exit_system(GameState::GameOver, despawn_screen::<KillFeedUi>);
+
+        #[cfg(feature = "minimap")]
+        app.add_enter_system(GameState::PreGame, minimap_setup)
+            .add_system(minimap.run_in_state(GameState::Running))
+            .add_exit_system(GameState::GameOver, despawn_screen::<MinimapUi>);
     }
 }
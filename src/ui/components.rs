@@ -1,9 +1,10 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Entity, Timer};
 
 // All actions that can be triggered from a button click
 #[derive(Component)]
 pub enum MenuButtonAction {
     NewGame,
+    Settings,
     BackToMainMenu,
     Quit,
 }
@@ -11,3 +12,86 @@ pub enum MenuButtonAction {
 // Tag component used to tag entities added on the main menu screen
 #[derive(Component)]
 pub struct OnMainMenuScreen;
+
+// Tag component used to tag entities added on the game over screen
+#[derive(Component)]
+pub struct OnGameOverScreen;
+
+// Tag component used to tag entities added on the settings screen
+#[derive(Component)]
+pub struct OnSettingsScreen;
+
+// One of the movement keybinds that can be changed on the settings screen
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BindingSlot {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+// Text showing the key bound to a slot
+#[derive(Component)]
+pub struct BindingText(pub BindingSlot);
+
+// Text telling the player what the settings screen is waiting for
+#[derive(Component)]
+pub struct RebindStatusText;
+
+// Tag component for the lines of the grid overlay
+#[derive(Component)]
+pub struct GridLine;
+
+// Tag component for the ghost head showing where the local snake moves next
+#[derive(Component)]
+pub struct MovePreview;
+
+// Tag component used to tag entities added on the countdown overlay
+#[derive(Component)]
+pub struct OnCountdownScreen;
+
+// Text showing the seconds left on the countdown
+#[derive(Component)]
+pub struct CountdownText;
+
+// Tag component used to tag entities added on the pause overlay
+#[derive(Component)]
+pub struct OnPausedScreen;
+
+// Tag component for the root node the scoreboard rows are spawned under
+#[derive(Component)]
+pub struct ScoreboardUi;
+
+// Text floating above the snake head it names
+#[derive(Component)]
+pub struct NameLabel {
+    pub head: Entity,
+}
+
+// Tag component for the root node of the minimap overlay
+#[cfg(feature = "minimap")]
+#[derive(Component)]
+pub struct MinimapUi;
+
+// Minimap dot standing in for a snake head or food item
+#[cfg(feature = "minimap")]
+#[derive(Component)]
+pub struct MinimapDot {
+    pub target: Entity,
+}
+
+// Tag component for the kill feed's container in the corner
+#[derive(Component)]
+pub struct KillFeedUi;
+
+// One line of the kill feed, remembering when its death happened so it can fade out
+#[derive(Component)]
+pub struct KillFeedRow {
+    pub time: f64,
+}
+
+// Multiplier floating up from a snake that extended its eat streak
+#[derive(Component)]
+pub struct StreakPopup {
+    pub timer: Timer,
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::common::resources::Countdown;
+use crate::ui::components::{CountdownText, OnCountdownScreen};
+use crate::ui::mainmenu::TEXT_COLOR;
+
+// Big number in the middle of the screen, over the snakes waiting to start
+pub fn countdown_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(OnCountdownScreen)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 160.0,
+                        color: TEXT_COLOR,
+                    },
+                ))
+                .insert(CountdownText);
+        });
+}
+
+// Shows the seconds left before the game starts
+pub fn countdown_ui(countdown: Option<Res<Countdown>>, mut texts: Query<&mut Text, With<CountdownText>>) {
+    let countdown = match countdown {
+        Some(countdown) if countdown.is_changed() => countdown,
+        _ => return,
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = countdown.remaining.to_string();
+    }
+}
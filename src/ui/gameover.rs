@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::snake::events::{CollisionCause, GameResultEvent};
+use crate::snake::resources::{LocalSnake, Scoreboard};
+use crate::state::GameState;
+use crate::ui::components::{MenuButtonAction, OnGameOverScreen};
+use crate::ui::mainmenu::{start_new_game, NORMAL_BUTTON, TEXT_COLOR};
+
+pub fn game_over_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<Scoreboard>,
+    local_snake: Res<LocalSnake>,
+    mut result_events: EventReader<GameResultEvent>,
+) {
+    let default_font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let button_style = Style {
+        size: Size::new(Val::Px(250.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font: default_font.clone(),
+        font_size: 40.0,
+        color: TEXT_COLOR,
+    };
+    // The last snake standing wins, and with no survivor the best score does
+    let survivor = result_events.iter().last().and_then(|result| result.winner);
+    let winner = survivor.map(|id| (id, scoreboard.scores.get(&id).copied().unwrap_or_default())).or_else(|| {
+        scoreboard
+            .scores
+            .iter()
+            .max_by_key(|(id, score)| (**score, std::cmp::Reverse(**id)))
+            .map(|(id, score)| (*id, *score))
+    });
+    let winner = match winner {
+        Some((id, score)) => format!("Winner: Snake {} ({})", id, score),
+        None => "No winner".to_string(),
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::SEA_GREEN.into(),
+            ..default()
+        })
+        .insert(OnGameOverScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(
+                TextBundle::from_section(
+                    "Game Over",
+                    TextStyle {
+                        font: default_font.clone(),
+                        font_size: 80.0,
+                        color: TEXT_COLOR,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(50.0)),
+                    ..default()
+                }),
+            );
+
+            parent.spawn_bundle(TextBundle::from_section(winner, button_text_style.clone()));
+            if let Some(cause) = scoreboard.death_causes.get(&local_snake.0) {
+                parent.spawn_bundle(TextBundle::from_section(
+                    death_message(*cause),
+                    TextStyle {
+                        font_size: 30.0,
+                        ..button_text_style.clone()
+                    },
+                ));
+            }
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: button_style.clone(),
+                    color: NORMAL_BUTTON.into(),
+                    ..default()
+                })
+                .insert(MenuButtonAction::NewGame)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle::from_section("Play Again", button_text_style.clone()));
+                });
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: button_style.clone(),
+                    color: NORMAL_BUTTON.into(),
+                    ..default()
+                })
+                .insert(MenuButtonAction::BackToMainMenu)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle::from_section("Main Menu", button_text_style.clone()));
+                });
+        });
+}
+
+// Line under the title saying what the local snake ran into
+fn death_message(cause: CollisionCause) -> String {
+    match cause {
+        CollisionCause::SelfBody => "You ran into yourself".to_string(),
+        CollisionCause::OtherSnake(id) => format!("You ran into Snake {}", id),
+        CollisionCause::Wall => "You ran into the wall".to_string(),
+        CollisionCause::Obstacle => "You ran into an obstacle".to_string(),
+    }
+}
+
+// Enter or Space restarts straight away without reaching for the mouse
+pub fn game_over_input(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    current_state: Res<CurrentState<GameState>>,
+    next_state: Option<Res<NextState<GameState>>>,
+) {
+    if keys.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        start_new_game(&mut commands, &current_state, next_state.as_deref());
+    }
+}
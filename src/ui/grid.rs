@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::common::resources::{ArenaConfig, RenderConfig, ShowGrid, Theme};
+use crate::ui::components::GridLine;
+
+const GRID_LINE_WIDTH: f32 = 1.0;
+
+pub fn toggle_grid(mut commands: Commands, keys: Res<Input<KeyCode>>, show_grid: Option<Res<ShowGrid>>) {
+    if keys.just_pressed(KeyCode::G) {
+        if show_grid.is_some() {
+            commands.remove_resource::<ShowGrid>();
+        } else {
+            commands.insert_resource(ShowGrid);
+        }
+    }
+}
+
+// Spawns a line along every cell boundary, respawning them if the arena, cell size or theme changes
+pub fn grid_overlay(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    render: Res<RenderConfig>,
+    theme: Res<Theme>,
+    lines: Query<Entity, With<GridLine>>,
+) {
+    if !lines.is_empty() {
+        if !arena.is_changed() && !render.is_changed() && !theme.is_changed() {
+            return;
+        }
+        for line in lines.iter() {
+            commands.entity(line).despawn();
+        }
+    }
+
+    let (width, height) = (
+        arena.width as f32 * render.cell_size,
+        arena.height as f32 * render.cell_size,
+    );
+    // Cell centers sit on multiples of cell_size around the origin, so boundaries are half a cell off them
+    let vertical = (0..=arena.width).map(|i| {
+        let x = (i as f32 - arena.width as f32 / 2.) * render.cell_size;
+        (Vec2::new(x, 0.), Vec2::new(GRID_LINE_WIDTH, height))
+    });
+    let horizontal = (0..=arena.height).map(|i| {
+        let y = (i as f32 - arena.height as f32 / 2.) * render.cell_size;
+        (Vec2::new(0., y), Vec2::new(width, GRID_LINE_WIDTH))
+    });
+    for (position, size) in vertical.chain(horizontal) {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: theme.grid_color,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(2.0)),
+                ..default()
+            })
+            .insert(GridLine);
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::snake::components::{SnakeHead, SnakeName};
+use crate::snake::events::{CollisionCause, SnakeDeathEvent};
+use crate::ui::components::{KillFeedRow, KillFeedUi};
+
+// Most deaths listed at once, dropping the oldest first
+const MAX_ENTRIES: usize = 5;
+// Seconds an entry stays listed, the last of which it spends fading out
+const ENTRY_LIFETIME_SECS: f64 = 5.0;
+const FADE_SECS: f64 = 1.0;
+const FONT_SIZE: f32 = 24.0;
+
+/// A snake's name as the kill feed shows it, in the snake's color
+#[derive(Clone)]
+pub struct FeedName {
+    pub name: String,
+    pub color: Color,
+}
+
+/// One death in the kill feed
+pub struct KillFeedEntry {
+    pub victim: FeedName,
+    pub cause: CollisionCause,
+    /// Snake that was run into, for [`CollisionCause::OtherSnake`]
+    pub killer: Option<FeedName>,
+    /// When it happened, in seconds since startup
+    pub time: f64,
+}
+
+/// Recent deaths, oldest first, shown in the corner during a game
+#[derive(Default)]
+pub struct KillFeed {
+    pub entries: VecDeque<KillFeedEntry>,
+}
+
+impl KillFeed {
+    pub fn push(&mut self, entry: KillFeedEntry) {
+        self.entries.push_back(entry);
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Whether any entry is old enough to drop as of `now`
+    pub fn has_expired(&self, now: f64) -> bool {
+        self.entries.front().is_some_and(|entry| now - entry.time >= ENTRY_LIFETIME_SECS)
+    }
+
+    /// Drops entries old enough to have faded out as of `now`
+    pub fn expire(&mut self, now: f64) {
+        while self.has_expired(now) {
+            self.entries.pop_front();
+        }
+    }
+}
+
+// Opacity of an entry added at `time`, fading out over its last FADE_SECS
+fn entry_alpha(time: f64, now: f64) -> f32 {
+    ((time + ENTRY_LIFETIME_SECS - now) / FADE_SECS).clamp(0., 1.) as f32
+}
+
+pub fn killfeed_setup(mut commands: Commands) {
+    commands.insert_resource(KillFeed::default());
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(KillFeedUi);
+}
+
+// Adds an entry for every snake that died this frame. Dead heads are already out of play by now, but keep their name
+// and sprite while they fade.
+pub fn record_kills(
+    time: Res<Time>,
+    mut feed: ResMut<KillFeed>,
+    mut death_events: EventReader<SnakeDeathEvent>,
+    heads: Query<(Entity, &SnakeHead)>,
+    names: Query<(&SnakeName, &Sprite)>,
+) {
+    let feed_name = |id: u8, head: Option<Entity>| {
+        let (name, color) = match head.and_then(|head| names.get(head).ok()) {
+            Some((name, sprite)) => (name.0.clone(), *sprite.color.clone().set_a(1.0)),
+            None => (String::new(), Color::WHITE),
+        };
+        FeedName {
+            name: if name.is_empty() { format!("Snake {}", id) } else { name },
+            color,
+        }
+    };
+    let head_of = |id: u8| heads.iter().find(|(_, head)| head.id == id).map(|(entity, _)| entity);
+    for event in death_events.iter() {
+        feed.push(KillFeedEntry {
+            victim: feed_name(event.id, Some(event.head)),
+            cause: event.cause,
+            killer: match event.cause {
+                CollisionCause::OtherSnake(killer) => Some(feed_name(killer, head_of(killer))),
+                _ => None,
+            },
+            time: time.seconds_since_startup(),
+        });
+    }
+}
+
+// Drops expired entries, rebuilds the rows whenever the feed changes, and fades each row out as it gets old
+pub fn killfeed_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    mut feed: ResMut<KillFeed>,
+    roots: Query<Entity, With<KillFeedUi>>,
+    mut rows: Query<(&KillFeedRow, &mut Text)>,
+) {
+    let now = time.seconds_since_startup();
+    // Only borrow mutably when something actually expires, so the rows are not rebuilt every frame
+    if feed.has_expired(now) {
+        feed.expire(now);
+    }
+
+    if feed.is_changed() {
+        let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+        let section = |value: String, color: Color| {
+            TextSection::new(
+                value,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: FONT_SIZE,
+                    color,
+                },
+            )
+        };
+        for root in roots.iter() {
+            let mut root = commands.entity(root);
+            root.despawn_descendants();
+            root.with_children(|parent| {
+                for entry in feed.entries.iter() {
+                    let victim = section(entry.victim.name.clone(), entry.victim.color);
+                    let sections = match (&entry.killer, entry.cause) {
+                        (Some(killer), _) => vec![
+                            section(killer.name.clone(), killer.color),
+                            section(" -> ".to_string(), Color::WHITE),
+                            victim,
+                        ],
+                        (None, CollisionCause::Wall) => {
+                            vec![victim, section(" hit the wall".to_string(), Color::WHITE)]
+                        }
+                        (None, CollisionCause::Obstacle) => {
+                            vec![victim, section(" hit an obstacle".to_string(), Color::WHITE)]
+                        }
+                        (None, _) => vec![victim, section(" ran into itself".to_string(), Color::WHITE)],
+                    };
+                    parent.spawn_bundle(TextBundle::from_sections(sections)).insert(KillFeedRow { time: entry.time });
+                }
+            });
+        }
+        return;
+    }
+
+    for (row, mut text) in rows.iter_mut() {
+        let alpha = entry_alpha(row.time, now);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u8, time: f64) -> KillFeedEntry {
+        KillFeedEntry {
+            victim: FeedName {
+                name: format!("Snake {}", id),
+                color: Color::WHITE,
+            },
+            cause: CollisionCause::Wall,
+            killer: None,
+            time,
+        }
+    }
+
+    fn victims(feed: &KillFeed) -> Vec<String> {
+        feed.entries.iter().map(|entry| entry.victim.name.clone()).collect()
+    }
+
+    #[test]
+    fn push_keeps_the_newest_entries() {
+        let mut feed = KillFeed::default();
+        for id in 0..7 {
+            feed.push(entry(id, 0.));
+        }
+        assert_eq!(feed.entries.len(), MAX_ENTRIES);
+        assert_eq!(victims(&feed), ["Snake 2", "Snake 3", "Snake 4", "Snake 5", "Snake 6"]);
+    }
+
+    #[test]
+    fn expire_drops_entries_past_their_lifetime() {
+        let mut feed = KillFeed::default();
+        feed.push(entry(0, 1.));
+        feed.push(entry(1, 2.));
+        feed.push(entry(2, 4.));
+
+        feed.expire(1. + ENTRY_LIFETIME_SECS - 0.1);
+        assert_eq!(feed.entries.len(), 3);
+        feed.expire(2. + ENTRY_LIFETIME_SECS);
+        assert_eq!(victims(&feed), ["Snake 2"]);
+        assert!(!feed.has_expired(4. + ENTRY_LIFETIME_SECS - 0.1));
+        feed.expire(100.);
+        assert!(feed.entries.is_empty());
+    }
+}
@@ -4,8 +4,8 @@ use bevy::app::AppExit;
 use bevy::prelude::*;
 use iyes_loopless::prelude::*;
 
-const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
@@ -65,6 +65,17 @@ pub fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                     parent.spawn_bundle(TextBundle::from_section("New Game", button_text_style.clone()));
                 });
 
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: button_style.clone(),
+                    color: NORMAL_BUTTON.into(),
+                    ..default()
+                })
+                .insert(MenuButtonAction::Settings)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle::from_section("Settings", button_text_style.clone()));
+                });
+
             parent
                 .spawn_bundle(ButtonBundle {
                     style: button_style.clone(),
@@ -91,13 +102,16 @@ pub fn button_system(mut interaction_query: Query<(&Interaction, &mut UiColor),
 
 pub fn menu_action(
     mut commands: Commands,
+    current_state: Res<CurrentState<GameState>>,
+    next_state: Option<Res<NextState<GameState>>>,
     interaction_query: Query<(&Interaction, &MenuButtonAction), (Changed<Interaction>, With<Button>)>,
     mut app_exit_events: EventWriter<AppExit>,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Clicked {
             match menu_button_action {
-                MenuButtonAction::NewGame => commands.insert_resource(NextState(GameState::PreGame)),
+                MenuButtonAction::NewGame => start_new_game(&mut commands, &current_state, next_state.as_deref()),
+                MenuButtonAction::Settings => commands.insert_resource(NextState(GameState::Settings)),
                 MenuButtonAction::BackToMainMenu => commands.insert_resource(NextState(GameState::MainMenu)),
                 MenuButtonAction::Quit => app_exit_events.send(AppExit),
             }
@@ -105,6 +119,23 @@ pub fn menu_action(
     }
 }
 
+// Requests a transition into PreGame, at most once, and only from a screen a new game can be started from
+pub fn start_new_game(
+    commands: &mut Commands,
+    current_state: &CurrentState<GameState>,
+    next_state: Option<&NextState<GameState>>,
+) {
+    if matches!(next_state, Some(NextState(GameState::PreGame))) {
+        return;
+    }
+    debug_assert!(
+        matches!(current_state.0, GameState::MainMenu | GameState::GameOver),
+        "Tried to start a new game from {:?}",
+        current_state.0
+    );
+    commands.insert_resource(NextState(GameState::PreGame));
+}
+
 // Generic system that takes a component as a parameter, and will despawn all entities with that component
 pub fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
     for entity in &to_despawn {
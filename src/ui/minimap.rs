@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::common::components::Position;
+use crate::common::resources::ArenaConfig;
+use crate::food::components::Food;
+use crate::snake::components::SnakeHead;
+use crate::snake::resources::LocalSnake;
+use crate::ui::components::{MinimapDot, MinimapUi};
+
+const MINIMAP_SIZE: f32 = 150.0;
+const MINIMAP_BACKGROUND: Color = Color::rgba(0.2, 0.2, 0.2, 0.7);
+const DOT_SIZE: f32 = 4.0;
+const LOCAL_DOT_SIZE: f32 = 8.0;
+
+pub fn minimap_setup(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(MINIMAP_SIZE), Val::Px(MINIMAP_SIZE)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            color: MINIMAP_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(MinimapUi);
+}
+
+// Keeps a dot on the minimap for every snake head and food item, scaled down from the arena. Dots follow what they
+// stand for and are only spawned and despawned as snakes and food come and go.
+pub fn minimap(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    local_snake: Option<Res<LocalSnake>>,
+    heads: Query<(Entity, &Position, &SnakeHead)>,
+    foods: Query<(Entity, &Position, &Sprite), With<Food>>,
+    roots: Query<Entity, With<MinimapUi>>,
+    mut dots: Query<(Entity, &MinimapDot, &mut Style)>,
+) {
+    let root = match roots.get_single() {
+        Ok(root) => root,
+        Err(_) => return,
+    };
+    let local_id = local_snake.map(|local_snake| local_snake.0);
+    let mut targets: HashMap<Entity, (Position, Color, f32)> = heads
+        .iter()
+        .map(|(entity, pos, head)| {
+            let size = if Some(head.id) == local_id { LOCAL_DOT_SIZE } else { DOT_SIZE };
+            (entity, (*pos, head.color, size))
+        })
+        .chain(foods.iter().map(|(entity, pos, sprite)| (entity, (*pos, sprite.color, DOT_SIZE))))
+        .collect();
+
+    for (entity, dot, mut style) in dots.iter_mut() {
+        match targets.remove(&dot.target) {
+            Some((pos, _, size)) => {
+                let placed = dot_style(pos, size, &arena);
+                // Only touch the style when the dot moved, so the UI isn't laid out again for nothing
+                if style.position != placed.position || style.size != placed.size {
+                    *style = placed;
+                }
+            }
+            // Recursive despawning also takes the dot out of the minimap's children
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    // Whatever is left has no dot yet
+    commands.entity(root).with_children(|parent| {
+        for (target, (pos, color, size)) in targets {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: dot_style(pos, size, &arena),
+                    color: color.into(),
+                    ..default()
+                })
+                .insert(MinimapDot { target });
+        }
+    });
+}
+
+// Places a dot of `size` pixels over the cell at `pos`
+fn dot_style(pos: Position, size: f32, arena: &ArenaConfig) -> Style {
+    Style {
+        size: Size::new(Val::Px(size), Val::Px(size)),
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            left: Val::Px((pos.x as f32 + 0.5) / arena.width as f32 * MINIMAP_SIZE - size / 2.),
+            bottom: Val::Px((pos.y as f32 + 0.5) / arena.height as f32 * MINIMAP_SIZE - size / 2.),
+            ..default()
+        },
+        ..default()
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::common::components::{Position, Size};
+use crate::common::resources::{ArenaConfig, ShowMovePreview, Spectator};
+use crate::snake::components::SnakeHead;
+use crate::snake::resources::LocalSnake;
+use crate::ui::components::MovePreview;
+
+const PREVIEW_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+
+pub fn toggle_move_preview(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    show_move_preview: Option<Res<ShowMovePreview>>,
+) {
+    if keys.just_pressed(KeyCode::V) {
+        if show_move_preview.is_some() {
+            commands.remove_resource::<ShowMovePreview>();
+        } else {
+            commands.insert_resource(ShowMovePreview);
+        }
+    }
+}
+
+// Keeps a ghost head one cell ahead of the local snake in the direction it will move next tick. Spectators have no
+// snake of their own, so they never get one.
+pub fn show_move_preview(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    local_snake: Res<LocalSnake>,
+    spectator: Option<Res<Spectator>>,
+    heads: Query<(&Position, &SnakeHead)>,
+    mut previews: Query<(Entity, &mut Position), (With<MovePreview>, Without<SnakeHead>)>,
+) {
+    let next = heads
+        .iter()
+        .find(|(_, head)| head.id == local_snake.0)
+        .filter(|_| spectator.is_none())
+        .map(|(position, head)| position.wrapped_step(head.input_direction, &arena));
+
+    match (next, previews.get_single_mut()) {
+        (Some(next), Ok((_, mut position))) => {
+            if *position != next {
+                *position = next;
+            }
+        }
+        (Some(next), Err(_)) => {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: PREVIEW_COLOR,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(MovePreview)
+                .insert(next)
+                .insert(Size::square(0.8));
+        }
+        (None, _) => {
+            for (preview, _) in previews.iter() {
+                commands.entity(preview).despawn();
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use crate::snake::components::{SnakeHead, SnakeName};
+use crate::snake::resources::LocalSnake;
+use crate::ui::components::NameLabel;
+
+const LABEL_FONT_SIZE: f32 = 16.0;
+// Only the bold font ships with the game, so the local player's name stands out by size instead
+const LOCAL_LABEL_FONT_SIZE: f32 = 22.0;
+
+// Keeps a name label floating above every snake head, spawning and despawning labels as snakes come and go
+pub fn render_name_labels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    local_snake: Res<LocalSnake>,
+    heads: Query<(Entity, &SnakeHead, &SnakeName, &Transform), Without<NameLabel>>,
+    mut labels: Query<(Entity, &NameLabel, &mut Transform)>,
+) {
+    let mut labelled = Vec::new();
+    for (entity, label, mut transform) in labels.iter_mut() {
+        match heads.get(label.head) {
+            Ok((_, _, _, head_transform)) => {
+                transform.translation = label_translation(head_transform);
+                labelled.push(label.head);
+            }
+            Err(_) => commands.entity(entity).despawn(),
+        }
+    }
+
+    for (entity, head, name, head_transform) in heads.iter().filter(|(entity, ..)| !labelled.contains(entity)) {
+        let text = if name.0.is_empty() { format!("Snake {}", head.id) } else { name.0.clone() };
+        let is_local = local_snake.0 == head.id;
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    text,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: if is_local { LOCAL_LABEL_FONT_SIZE } else { LABEL_FONT_SIZE },
+                        color: head.color,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(label_translation(head_transform)),
+                ..default()
+            })
+            .insert(NameLabel { head: entity });
+    }
+}
+
+// One cell above the head, drawn over the sprites
+#[inline]
+fn label_translation(head_transform: &Transform) -> Vec3 {
+    Vec3::new(
+        head_transform.translation.x,
+        head_transform.translation.y + head_transform.scale.y,
+        10.0,
+    )
+}
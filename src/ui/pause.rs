@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::state::GameState;
+use crate::ui::components::OnPausedScreen;
+use crate::ui::mainmenu::TEXT_COLOR;
+
+const PAUSED_OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+// Escape pauses a running game and resumes a paused one
+pub fn toggle_pause(mut commands: Commands, keys: Res<Input<KeyCode>>, state: Res<CurrentState<GameState>>) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    match state.0 {
+        GameState::Running => commands.insert_resource(NextState(GameState::Paused)),
+        GameState::Paused => commands.insert_resource(NextState(GameState::Running)),
+        _ => {}
+    }
+}
+
+// Dims the whole screen and shows "Paused" over the frozen game
+pub fn paused_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: PAUSED_OVERLAY_COLOR.into(),
+            ..default()
+        })
+        .insert(OnPausedScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 80.0,
+                    color: TEXT_COLOR,
+                },
+            ));
+        });
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+
+use crate::snake::components::SnakeHead;
+use crate::snake::resources::{LocalSnake, RespawnConfig, Scoreboard, SnakeRegistry};
+use crate::ui::components::ScoreboardUi;
+use crate::ui::mainmenu::TEXT_COLOR;
+
+const LOCAL_ROW_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+
+pub fn scoreboard_setup(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(ScoreboardUi);
+}
+
+// Rebuilds the scoreboard rows, only when a score actually changed
+#[allow(clippy::too_many_arguments)]
+pub fn update_scoreboard_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<Scoreboard>,
+    respawn_config: Res<RespawnConfig>,
+    local_snake: Res<LocalSnake>,
+    registry: Res<SnakeRegistry>,
+    heads: Query<&SnakeHead>,
+    roots: Query<Entity, With<ScoreboardUi>>,
+) {
+    if !scoreboard.is_changed() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    // Teams in order with their snakes grouped under them, followed by snakes without a team
+    let team = |id: &u8| scoreboard.teams.get(id).copied();
+    let mut scores: Vec<_> = scoreboard.scores.iter().collect();
+    scores.sort_by_key(|(id, _)| (team(id).is_none(), team(id), **id));
+    for root in roots.iter() {
+        let mut root = commands.entity(root);
+        root.despawn_descendants();
+        root.with_children(|parent| {
+            let mut current_team = None;
+            for (id, score) in scores.iter() {
+                if let Some(team) = team(id).filter(|team| current_team != Some(*team)) {
+                    current_team = Some(team);
+                    let total: u32 = scores
+                        .iter()
+                        .filter(|(id, _)| scoreboard.teams.get(id) == Some(&team))
+                        .map(|(_, score)| **score)
+                        .sum();
+                    parent.spawn_bundle(TextBundle::from_section(
+                        format!("Team {}: {}", team, total),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 34.0,
+                            color: TEXT_COLOR,
+                        },
+                    ));
+                }
+                let color = registry
+                    .snakes
+                    .get(*id)
+                    .and_then(|head| heads.get(*head).ok())
+                    .map_or(TEXT_COLOR, |head| head.color);
+                let row_color = if **id == local_snake.0 { LOCAL_ROW_COLOR } else { Color::NONE };
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                        color: row_color.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle::from_section(
+                            row_text(**id, **score, &scoreboard, respawn_config.enabled),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 30.0,
+                                color,
+                            },
+                        ));
+                    });
+            }
+        });
+    }
+}
+
+// e.g. "Snake 2: 5 (1 kill, 3 deaths)", leaving out kills until there are some and deaths unless snakes respawn
+fn row_text(id: u8, score: u32, scoreboard: &Scoreboard, show_deaths: bool) -> String {
+    let mut stats = Vec::new();
+    if let Some(kills) = scoreboard.kills.get(&id) {
+        stats.push(counted(*kills, "kill"));
+    }
+    if let Some(deaths) = scoreboard.deaths.get(&id).filter(|_| show_deaths) {
+        stats.push(counted(*deaths, "death"));
+    }
+    if stats.is_empty() {
+        format!("Snake {}: {}", id, score)
+    } else {
+        format!("Snake {}: {} ({})", id, score, stats.join(", "))
+    }
+}
+
+// e.g. "1 kill" or "2 kills"
+fn counted(count: u32, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_text_pluralizes_stats() {
+        let mut scoreboard = Scoreboard::default();
+        assert_eq!(row_text(2, 5, &scoreboard, true), "Snake 2: 5");
+        scoreboard.kills.insert(2, 1);
+        scoreboard.deaths.insert(2, 3);
+        assert_eq!(row_text(2, 5, &scoreboard, true), "Snake 2: 5 (1 kill, 3 deaths)");
+        assert_eq!(row_text(2, 5, &scoreboard, false), "Snake 2: 5 (1 kill)");
+        scoreboard.kills.insert(2, 2);
+        scoreboard.deaths.insert(2, 1);
+        assert_eq!(row_text(2, 5, &scoreboard, true), "Snake 2: 5 (2 kills, 1 death)");
+    }
+}
@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+
+use crate::snake::resources::MovementKeybinds;
+use crate::ui::components::{BindingSlot, BindingText, MenuButtonAction, OnSettingsScreen, RebindStatusText};
+use crate::ui::mainmenu::{NORMAL_BUTTON, TEXT_COLOR};
+
+const IDLE_STATUS: &str = "Click a binding to change it";
+
+// Keys that already do something while playing (pause, theme, grid, move preview, zoom, console), so a movement
+// bound to one would trigger both
+const RESERVED_KEYS: [KeyCode; 9] = [
+    KeyCode::Escape,
+    KeyCode::T,
+    KeyCode::G,
+    KeyCode::V,
+    KeyCode::Equals,
+    KeyCode::NumpadAdd,
+    KeyCode::Minus,
+    KeyCode::NumpadSubtract,
+    KeyCode::Grave,
+];
+
+impl BindingSlot {
+    const ALL: [BindingSlot; 4] = [
+        BindingSlot::Up,
+        BindingSlot::Down,
+        BindingSlot::Left,
+        BindingSlot::Right,
+    ];
+
+    fn key(self, keybinds: &MovementKeybinds) -> KeyCode {
+        match self {
+            BindingSlot::Up => keybinds.up,
+            BindingSlot::Down => keybinds.down,
+            BindingSlot::Left => keybinds.left,
+            BindingSlot::Right => keybinds.right,
+        }
+    }
+
+    fn key_mut(self, keybinds: &mut MovementKeybinds) -> &mut KeyCode {
+        match self {
+            BindingSlot::Up => &mut keybinds.up,
+            BindingSlot::Down => &mut keybinds.down,
+            BindingSlot::Left => &mut keybinds.left,
+            BindingSlot::Right => &mut keybinds.right,
+        }
+    }
+}
+
+/// Slot waiting for the next key press on the settings screen, if any
+#[derive(Default)]
+pub struct Rebinding {
+    pub slot: Option<BindingSlot>,
+}
+
+pub fn settings_setup(mut commands: Commands, asset_server: Res<AssetServer>, keybinds: Res<MovementKeybinds>) {
+    commands.insert_resource(Rebinding::default());
+    let default_font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let button_style = Style {
+        size: Size::new(Val::Px(350.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font: default_font.clone(),
+        font_size: 40.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::SEA_GREEN.into(),
+            ..default()
+        })
+        .insert(OnSettingsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(
+                TextBundle::from_section(
+                    "Controls",
+                    TextStyle {
+                        font: default_font.clone(),
+                        font_size: 80.0,
+                        color: TEXT_COLOR,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(30.0)),
+                    ..default()
+                }),
+            );
+
+            for slot in BindingSlot::ALL {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: button_style.clone(),
+                        color: NORMAL_BUTTON.into(),
+                        ..default()
+                    })
+                    .insert(slot)
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(TextBundle::from_section(
+                                binding_label(slot, &keybinds),
+                                button_text_style.clone(),
+                            ))
+                            .insert(BindingText(slot));
+                    });
+            }
+
+            parent
+                .spawn_bundle(
+                    TextBundle::from_section(
+                        IDLE_STATUS,
+                        TextStyle {
+                            font: default_font.clone(),
+                            font_size: 25.0,
+                            color: TEXT_COLOR,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    }),
+                )
+                .insert(RebindStatusText);
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: button_style.clone(),
+                    color: NORMAL_BUTTON.into(),
+                    ..default()
+                })
+                .insert(MenuButtonAction::BackToMainMenu)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle::from_section("Back", button_text_style.clone()));
+                });
+        });
+}
+
+// Clicking a binding waits for the next key press, which replaces it unless the key is reserved or another direction
+// already uses it. Escape cancels.
+pub fn rebind_ui(
+    keys: Res<Input<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut keybinds: ResMut<MovementKeybinds>,
+    slots: Query<(&Interaction, &BindingSlot), (Changed<Interaction>, With<Button>)>,
+    mut binding_texts: Query<(&BindingText, &mut Text), Without<RebindStatusText>>,
+    mut status_texts: Query<&mut Text, With<RebindStatusText>>,
+) {
+    let mut status = None;
+    for (interaction, slot) in slots.iter() {
+        if *interaction == Interaction::Clicked {
+            rebinding.slot = Some(*slot);
+            status = Some(format!("Press a key for {:?}, or Escape to cancel", slot));
+        }
+    }
+
+    if let (Some(slot), Some(key)) = (rebinding.slot, keys.get_just_pressed().next()) {
+        if *key == KeyCode::Escape {
+            rebinding.slot = None;
+            status = Some(IDLE_STATUS.to_string());
+        } else {
+            match rebind(slot, *key, &mut keybinds) {
+                Ok(()) => {
+                    rebinding.slot = None;
+                    status = Some(IDLE_STATUS.to_string());
+                }
+                Err(reason) => status = Some(reason),
+            }
+        }
+    }
+
+    if let Some(status) = status {
+        for mut text in status_texts.iter_mut() {
+            text.sections[0].value = status.clone();
+        }
+    }
+    if keybinds.is_changed() {
+        for (binding, mut text) in binding_texts.iter_mut() {
+            text.sections[0].value = binding_label(binding.0, &keybinds);
+        }
+    }
+}
+
+// Binds `key` to `slot`, or says why it can't be
+fn rebind(slot: BindingSlot, key: KeyCode, keybinds: &mut MovementKeybinds) -> Result<(), String> {
+    if RESERVED_KEYS.contains(&key) {
+        return Err(format!("{:?} is reserved", key));
+    }
+    if let Some(other) = BindingSlot::ALL.into_iter().find(|other| *other != slot && other.key(keybinds) == key) {
+        return Err(format!("{:?} is already bound to {:?}", key, other));
+    }
+    *slot.key_mut(keybinds) = key;
+    Ok(())
+}
+
+#[inline]
+fn binding_label(slot: BindingSlot, keybinds: &MovementKeybinds) -> String {
+    format!("{:?}: {:?}", slot, slot.key(keybinds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebind_rejects_reserved_and_taken_keys() {
+        let mut keybinds = MovementKeybinds::default();
+
+        assert!(rebind(BindingSlot::Up, KeyCode::T, &mut keybinds).is_err());
+        assert!(rebind(BindingSlot::Up, KeyCode::Minus, &mut keybinds).is_err());
+        assert!(rebind(BindingSlot::Up, KeyCode::Left, &mut keybinds).is_err());
+        assert_eq!(keybinds.up, KeyCode::Up);
+
+        assert!(rebind(BindingSlot::Up, KeyCode::W, &mut keybinds).is_ok());
+        assert_eq!(keybinds.up, KeyCode::W);
+        // Rebinding a slot to its own key is a no-op rather than a conflict
+        assert!(rebind(BindingSlot::Left, KeyCode::Left, &mut keybinds).is_ok());
+    }
+}
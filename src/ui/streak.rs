@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::food::events::StreakEvent;
+use crate::snake::components::SnakeHead;
+use crate::snake::resources::SnakeRegistry;
+use crate::ui::components::StreakPopup;
+
+const POPUP_FONT_SIZE: f32 = 28.0;
+// How long a popup lasts, and how fast it floats up in pixels per second as it fades
+const POPUP_SECS: f32 = 1.0;
+const POPUP_RISE_SPEED: f32 = 40.0;
+
+// Pops up the multiplier over a snake's head whenever its eat streak grows
+pub fn show_streaks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<SnakeRegistry>,
+    mut streak_events: EventReader<StreakEvent>,
+    heads: Query<(&SnakeHead, &Transform)>,
+) {
+    for event in streak_events.iter() {
+        let (head, transform) = match registry.snakes.get(&event.id).and_then(|head| heads.get(*head).ok()) {
+            Some(head) => head,
+            None => continue,
+        };
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    format!("x{}", event.multiplier),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: POPUP_FONT_SIZE,
+                        color: head.color,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                // Above the head, and above its name label
+                transform: Transform::from_xyz(
+                    transform.translation.x,
+                    transform.translation.y + transform.scale.y * 2.,
+                    11.0,
+                ),
+                ..default()
+            })
+            .insert(StreakPopup {
+                timer: Timer::from_seconds(POPUP_SECS, false),
+            });
+    }
+}
+
+// Floats popups up while fading them out
+pub fn animate_streak_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut StreakPopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        if popup.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(popup.timer.percent_left());
+        }
+    }
+}